@@ -16,17 +16,184 @@ enum Day03Error {
     /// Attempted to process the index for an invalid letter
     InvalidLetterIndex(u8),
 
-    /// Multiple duplicate letters found in this chunk
-    MultipleDuplicateLettersFoundInLine(&'static str, &'static str),
+    /// Multiple duplicate letters found in the two halves of this line
+    MultipleDuplicateLettersFoundInLine(&'static [u8], &'static [u8]),
 
     /// Multiple duplicate letters found in this chunk
     MultipleDuplicateLettersFoundInChunk(&'static str, &'static str, &'static str),
 
-    /// No duplicate letters found in this line
-    NoDuplicateLettersFoundInLine(&'static str, &'static str),
+    /// No duplicate letters found in the two halves of this line. The halves are
+    /// bytes since a line with non-ASCII characters may split inside one.
+    NoDuplicateLettersFoundInLine(&'static [u8], &'static [u8]),
 
     /// No duplicate letters found in this chunk
     NoDuplicateLettersFoundInChunk(&'static str, &'static str, &'static str),
+
+    /// The input ends with a group of fewer than three lines
+    IncompleteGroup(usize),
+
+    /// The input failed validation with the given number of problems
+    InvalidInput(usize),
+}
+
+impl std::fmt::Display for Day03Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Day03Error::OddLengthInput(line) => {
+                write!(f, "odd length line ({} bytes): {line:?}", line.len())
+            }
+            Day03Error::InvalidChunk(input) => write!(f, "invalid chunk: {input:?}"),
+            Day03Error::InvalidLetterIndex(letter) => {
+                write!(f, "invalid letter {:?} (0x{letter:02x})", *letter as char)
+            }
+            Day03Error::MultipleDuplicateLettersFoundInLine(left, right) => write!(
+                f,
+                "multiple duplicate letters in \"{}\" | \"{}\"",
+                left.escape_ascii(),
+                right.escape_ascii()
+            ),
+            Day03Error::MultipleDuplicateLettersFoundInChunk(first, second, third) => write!(
+                f,
+                "multiple common letters in group {first:?}, {second:?}, {third:?}"
+            ),
+            Day03Error::NoDuplicateLettersFoundInLine(left, right) => write!(
+                f,
+                "no duplicate letters in \"{}\" | \"{}\"",
+                left.escape_ascii(),
+                right.escape_ascii()
+            ),
+            Day03Error::NoDuplicateLettersFoundInChunk(first, second, third) => write!(
+                f,
+                "no common letters in group {first:?}, {second:?}, {third:?}"
+            ),
+            Day03Error::IncompleteGroup(lines) => {
+                write!(f, "incomplete trailing group of {lines} line(s)")
+            }
+            Day03Error::InvalidInput(count) => write!(f, "input has {count} problem(s)"),
+        }
+    }
+}
+
+/// A single problem found while validating the puzzle input
+#[derive(Debug)]
+struct Problem {
+    /// The 1-indexed line number where the problem was found
+    line: usize,
+
+    /// The problem found on this line
+    error: Day03Error,
+}
+
+/// Every problem found in an input, rather than only the first one
+#[derive(Debug, Default)]
+struct ValidationReport {
+    /// The found problems, ordered by line number
+    problems: Vec<Problem>,
+}
+
+impl ValidationReport {
+    /// Record the `error` found on the 1-indexed `line`
+    fn push(&mut self, line: usize, error: Day03Error) {
+        self.problems.push(Problem { line, error });
+    }
+
+    /// Returns `true` if no problems were found
+    fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+impl std::fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for Problem { line, error } in &self.problems {
+            writeln!(f, "line {line}: {error}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Check the entire `input` for problems that `part1` and `part2` would abort on,
+/// collecting all of them instead of stopping at the first one
+fn validate(input: &'static str) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    // Bitmask of the letters found in each line, indexed by line. Lines with invalid
+    // letters only contribute their valid letters.
+    let mut line_letters = Vec::new();
+
+    for (index, line) in input.lines().enumerate() {
+        let line_number = index + 1;
+
+        // Report every invalid letter, not just the first one
+        let mut letters = 0u64;
+        for letter in line.bytes() {
+            match letter_score(letter) {
+                Ok(score) => letters |= 1 << score,
+                Err(err) => report.push(line_number, err),
+            }
+        }
+        line_letters.push(letters);
+
+        // A line that can't be split evenly can't be checked for duplicates
+        if line.len() % 2 == 1 {
+            report.push(line_number, Day03Error::OddLengthInput(line));
+            continue;
+        }
+
+        // Split the bytes, since the middle of a line with a non-ASCII character isn't
+        // always a character boundary
+        let (left, right) = line.as_bytes().split_at(line.len() / 2);
+        let left_letters = left
+            .iter()
+            .filter_map(|letter| letter_score(*letter).ok())
+            .fold(0u64, |acc, score| acc | 1 << score);
+        let right_letters = right
+            .iter()
+            .filter_map(|letter| letter_score(*letter).ok())
+            .fold(0u64, |acc, score| acc | 1 << score);
+
+        match (left_letters & right_letters).count_ones() {
+            0 => report.push(
+                line_number,
+                Day03Error::NoDuplicateLettersFoundInLine(left, right),
+            ),
+            1 => {}
+            _ => report.push(
+                line_number,
+                Day03Error::MultipleDuplicateLettersFoundInLine(left, right),
+            ),
+        }
+    }
+
+    // Check each group of three lines for exactly one common letter
+    let lines = input.lines().collect::<Vec<_>>();
+    for (group_index, group) in line_letters.chunks(3).enumerate() {
+        let line_number = group_index * 3 + 1;
+        let group_lines = &lines[group_index * 3..group_index * 3 + group.len()];
+
+        let [first, second, third] = group_lines else {
+            report.push(line_number, Day03Error::IncompleteGroup(group_lines.len()));
+            continue;
+        };
+
+        match (group[0] & group[1] & group[2]).count_ones() {
+            0 => report.push(
+                line_number,
+                Day03Error::NoDuplicateLettersFoundInChunk(first, second, third),
+            ),
+            1 => {}
+            _ => report.push(
+                line_number,
+                Day03Error::MultipleDuplicateLettersFoundInChunk(first, second, third),
+            ),
+        }
+    }
+
+    // Keep the report ordered by line number since the group checks run afterwards
+    report.problems.sort_by_key(|problem| problem.line);
+
+    report
 }
 
 /// Return the score of the given `byte` based on the following criteria from the puzzle:
@@ -56,12 +223,12 @@ fn part1() -> Result<usize, Day03Error> {
             return Err(Day03Error::OddLengthInput(line));
         }
 
-        // Split the input at the mid point
-        let (left, right) = line.split_at(line.len() / 2);
+        // Split the input at the mid point. Use the bytes here since the letters are
+        // all ASCII, and the mid point of anything else may not be a char boundary
+        let (left, right) = line.as_bytes().split_at(line.len() / 2);
 
-        // Use .bytes here since we know the input is a UTF8 string and not unicode
         // Set each letter in the left side as seen
-        for letter in left.bytes() {
+        for &letter in left {
             seen[letter as usize] = true;
         }
 
@@ -70,7 +237,7 @@ fn part1() -> Result<usize, Day03Error> {
         // For each letter in the right side, check if it has already been seen on the
         // left side. Also, sanity check that there is only one duplicate letter in the
         // right side and not multiple duplicate letters
-        for letter in right.bytes() {
+        for &letter in right {
             if seen[letter as usize] {
                 if let Some(already_found) = duplicate_letter {
                     if already_found != letter {
@@ -154,6 +321,13 @@ fn part2() -> Result<usize, Day03Error> {
 }
 
 fn main() -> Result<(), Day03Error> {
+    // Validate the entire input up front so every problem is reported in one go
+    let report = validate(INPUT);
+    if !report.is_ok() {
+        eprint!("{report}");
+        return Err(Day03Error::InvalidInput(report.problems.len()));
+    }
+
     let part1_score = part1()?;
     println!("Day 03 Part 1 {part1_score}");
 
@@ -162,3 +336,33 @@ fn main() -> Result<(), Day03Error> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A non-ASCII character is reported, even when the middle of its line falls
+    /// inside it
+    #[test]
+    fn validate_non_ascii() {
+        let report = validate("aéb\nvJrwpWtwJgWrhcsFMMfFFhFp\n");
+        let problems = report
+            .problems
+            .iter()
+            .map(|problem| (problem.line, problem.error.to_string()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            problems,
+            [
+                (1, "invalid letter 'Ã' (0xc3)".to_string()),
+                (1, "invalid letter '©' (0xa9)".to_string()),
+                (
+                    1,
+                    "no duplicate letters in \"a\\xc3\" | \"\\xa9b\"".to_string()
+                ),
+                (1, "incomplete trailing group of 2 line(s)".to_string()),
+            ]
+        );
+    }
+}
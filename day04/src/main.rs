@@ -1,3 +1,5 @@
+use aoc2022::interval::{Interval, IntervalSet};
+
 /// The input puzzle
 const INPUT: &str = include_str!("../input");
 
@@ -6,55 +8,144 @@ const INPUT: &str = include_str!("../input");
 enum Day04Error {
     InvalidLineFormat(&'static str),
     ParseIntError(std::num::ParseIntError),
+    InvalidRange(&'static str),
+}
+
+impl std::fmt::Display for Day04Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Day04Error::InvalidLineFormat(line) => write!(f, "invalid line format: {line:?}"),
+            Day04Error::ParseIntError(err) => write!(f, "{err}"),
+            Day04Error::InvalidRange(range) => write!(f, "range ends before it starts: {range:?}"),
+        }
+    }
+}
+
+/// Parse a section range of the form `2-4`
+fn parse_range(line: &'static str, range: &'static str) -> Result<Interval, Day04Error> {
+    let (min, max) = range
+        .split_once('-')
+        .ok_or(Day04Error::InvalidLineFormat(line))?;
+
+    let min = min.parse::<i64>().map_err(Day04Error::ParseIntError)?;
+    let max = max.parse::<i64>().map_err(Day04Error::ParseIntError)?;
+
+    Interval::new(min, max).ok_or(Day04Error::InvalidRange(range))
+}
+
+/// The section assignments of a single crew of elves (one input line)
+#[derive(Debug)]
+struct Crew {
+    /// The section range assigned to each elf in the crew
+    assignments: Vec<Interval>,
+}
+
+impl Crew {
+    /// Parse a crew line of the form `2-4,6-8,3-5` with any number of ranges
+    fn parse(line: &'static str) -> Result<Self, Day04Error> {
+        let assignments = line
+            .split(',')
+            .map(|range| parse_range(line, range))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // A crew needs at least two elves for the analysis to make sense
+        if assignments.len() < 2 {
+            return Err(Day04Error::InvalidLineFormat(line));
+        }
+
+        Ok(Self { assignments })
+    }
+
+    /// The indexes of the assignments whose sections are all covered by the other
+    /// elves in the crew
+    fn redundant(&self) -> Vec<usize> {
+        (0..self.assignments.len())
+            .filter(|index| {
+                let others = self
+                    .assignments
+                    .iter()
+                    .enumerate()
+                    .filter(|(other_index, _)| other_index != index)
+                    .map(|(_, other)| *other)
+                    .collect::<IntervalSet>();
+
+                others.contains_interval(&self.assignments[*index])
+            })
+            .collect()
+    }
+
+    /// The overlap graph of the crew as the index pairs `(a, b)` with `a < b` of every
+    /// two assignments sharing at least one section
+    fn overlaps(&self) -> Vec<(usize, usize)> {
+        let mut edges = Vec::new();
+
+        for (left_index, left) in self.assignments.iter().enumerate() {
+            for (right_index, right) in self.assignments.iter().enumerate().skip(left_index + 1) {
+                if left.overlaps(right) {
+                    edges.push((left_index, right_index));
+                }
+            }
+        }
+
+        edges
+    }
+
+    /// The sections between the lowest and highest assigned section that no elf in the
+    /// crew is assigned to
+    fn uncovered(&self) -> IntervalSet {
+        let covered = self.assignments.iter().copied().collect::<IntervalSet>();
+
+        let (Some(first), Some(last)) = (covered.intervals().first(), covered.intervals().last())
+        else {
+            return IntervalSet::new();
+        };
+
+        let span =
+            Interval::new(first.start(), last.end()).expect("Merged intervals are always sorted");
+
+        std::iter::once(span)
+            .collect::<IntervalSet>()
+            .difference(&covered)
+    }
 }
 
 fn main() -> Result<(), Day04Error> {
+    // Print the analysis for every crew with `--report`
+    let report = std::env::args().any(|arg| arg == "--report");
+
     let mut part1_count = 0;
     let mut part2_count = 0;
 
     // Each line has the form:
     // 1-22,333-44
     //
-    // Split each line into the pairs of numbers and check for overlapping or fully
-    // contained pairs
-    for line in INPUT.lines() {
-        let (left, right) = line
-            .split_once(',')
-            .ok_or(Day04Error::InvalidLineFormat(line))?;
-
-        let (left_min, left_max) = left
-            .split_once('-')
-            .ok_or(Day04Error::InvalidLineFormat(line))?;
-
-        let left_min = left_min.parse::<u32>().map_err(Day04Error::ParseIntError)?;
-
-        let left_max = left_max.parse::<u32>().map_err(Day04Error::ParseIntError)?;
+    // with any number of ranges per line. Check each crew for fully redundant
+    // assignments and overlapping pairs
+    for (index, line) in INPUT.lines().enumerate() {
+        let crew = Crew::parse(line)?;
 
-        let (right_min, right_max) = right
-            .split_once('-')
-            .ok_or(Day04Error::InvalidLineFormat(line))?;
+        let redundant = crew.redundant();
+        let overlaps = crew.overlaps();
 
-        let right_min = right_min
-            .parse::<u32>()
-            .map_err(Day04Error::ParseIntError)?;
-
-        let right_max = right_max
-            .parse::<u32>()
-            .map_err(Day04Error::ParseIntError)?;
-
-        // Count the number of pairs where one pair contains the other
-        if (left_min <= right_min && left_max >= right_max)
-            || (right_min <= left_min && right_max >= left_max)
-        {
+        // Count the number of crews where one assignment is covered by the others
+        if !redundant.is_empty() {
             part1_count += 1;
         }
 
-        // Count the number of pairs where the pairs overlap
-        if (left_min <= right_min && left_max >= right_min)
-            || (right_min <= left_min && right_max >= left_min)
-        {
+        // Count the number of crews where any two assignments overlap
+        if !overlaps.is_empty() {
             part2_count += 1;
         }
+
+        if report {
+            let uncovered = crew.uncovered();
+            println!(
+                "line {}: {line} | redundant: {redundant:?} | overlaps: {overlaps:?} | \
+                 uncovered: {} [{uncovered}]",
+                index + 1,
+                uncovered.total_len()
+            );
+        }
     }
 
     println!("Part 1 count: {part1_count}");
@@ -0,0 +1,143 @@
+//! Size the compile time solver from the input
+//!
+//! The input is read from the path in the `DAY05_INPUT` environment variable, or the
+//! `input` file next to this script. It is validated with the runtime parser and its
+//! dimensions are written to `$OUT_DIR/dimensions.rs` for the `const fn` solver. An
+//! input that can't be solved results in a `compile_error!` instead of a const-eval
+//! panic.
+
+use std::path::{Path, PathBuf};
+
+#[path = "src/parse.rs"]
+mod parse;
+
+/// The dimensions of an input needed to size the `const` arrays of the solver
+struct Dimensions {
+    /// The number of columns in the drawing
+    columns: usize,
+
+    /// The number of crate rows in the drawing
+    drawing_rows: usize,
+
+    /// The max height of any column at any point while executing the moves
+    column_height: usize,
+}
+
+/// Check that every instruction line has the exact format read by the `const fn`
+/// solver: `move N from A to B` with single spaces, or an empty line. The runtime
+/// parser is more lenient about whitespace.
+fn check_exact_moves(input: &str) -> Result<(), String> {
+    let (drawing, moves) = input
+        .split_once("\n\n")
+        .expect("Separator checked by the parser");
+
+    // The instructions start after the drawing and the empty line
+    let first_line = drawing.lines().count() + 2;
+    for (index, line) in moves.split('\n').enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+
+        let number = |value: &str| !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit());
+        let exact = line
+            .strip_prefix("move ")
+            .and_then(|rest| rest.split_once(" from "))
+            .and_then(|(count, rest)| Some((count, rest.split_once(" to ")?)))
+            .is_some_and(|(count, (src, dst))| number(count) && number(src) && number(dst));
+
+        if !exact {
+            return Err(format!(
+                "line {}: expected exactly `move N from A to B` with single spaces, found {line:?}",
+                first_line + index
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse the input at `path` and execute its moves to find its dimensions
+fn dimensions(path: &Path) -> Result<Dimensions, String> {
+    let input = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+
+    // The const parser doesn't handle `\r\n` line endings
+    if input.contains('\r') {
+        return Err("input has `\\r\\n` line endings".to_string());
+    }
+
+    let (stacks, moves) = parse::parse_input(&input).map_err(|err| err.to_string())?;
+    check_exact_moves(&input)?;
+
+    // The drawing ends at the first empty line and its last line is the column numbers
+    let drawing_rows = input
+        .lines()
+        .position(str::is_empty)
+        .expect("Separator checked by the parser")
+        - 1;
+
+    // Both crane models change the column heights the same way, so only the heights
+    // need to be tracked to validate the moves
+    let mut heights = stacks.iter().map(Vec::len).collect::<Vec<_>>();
+    let mut column_height = heights.iter().copied().max().unwrap_or(0);
+    for (index, mv) in moves.iter().enumerate() {
+        if heights[mv.src] < mv.count {
+            return Err(format!(
+                "instruction {} (`{mv}`) moves {} crates but column {} only has {}",
+                index + 1,
+                mv.count,
+                mv.src + 1,
+                heights[mv.src]
+            ));
+        }
+
+        heights[mv.src] -= mv.count;
+        heights[mv.dst] += mv.count;
+        column_height = column_height.max(heights[mv.dst]);
+    }
+
+    Ok(Dimensions {
+        columns: stacks.len(),
+        drawing_rows,
+        column_height: column_height.max(drawing_rows),
+    })
+}
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=DAY05_INPUT");
+    println!("cargo:rerun-if-changed=src/parse.rs");
+
+    let manifest_dir = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap());
+    let input_path =
+        std::env::var_os("DAY05_INPUT").map_or_else(|| manifest_dir.join("input"), PathBuf::from);
+    let input_path = manifest_dir.join(input_path);
+    println!("cargo:rerun-if-changed={}", input_path.display());
+
+    let generated = match dimensions(&input_path) {
+        Ok(Dimensions {
+            columns,
+            drawing_rows,
+            column_height,
+        }) => format!(
+            "const NUMBER_OF_COLUMNS: usize = {columns};\n\
+             const DRAWING_ROWS: usize = {drawing_rows};\n\
+             const COLUMN_HEIGHT: usize = {column_height};\n\
+             const INPUT: &[u8] = include_bytes!({:?});\n",
+            input_path.display().to_string()
+        ),
+        Err(err) => {
+            let message = format!("invalid day05 input {}: {err}", input_path.display());
+
+            // Use an empty input so the const solver has nothing to panic on
+            format!(
+                "compile_error!({message:?});\n\
+                 const NUMBER_OF_COLUMNS: usize = 0;\n\
+                 const DRAWING_ROWS: usize = 0;\n\
+                 const COLUMN_HEIGHT: usize = 0;\n\
+                 const INPUT: &[u8] = b\"\\n\\n\";\n"
+            )
+        }
+    };
+
+    let out_path = PathBuf::from(std::env::var_os("OUT_DIR").unwrap()).join("dimensions.rs");
+    std::fs::write(out_path, generated).expect("Failed to write dimensions.rs");
+}
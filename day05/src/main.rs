@@ -1,15 +1,50 @@
-#![feature(iter_next_chunk)]
-#![feature(const_mut_refs)]
-#![feature(const_option)]
+mod parse;
+mod yard;
 
-/// The puzzle input
-const INPUT: &[u8] = include_bytes!("../input");
+use parse::{Move, ParseError};
+use yard::{render_drawing, Crane, CrateMover9000, CrateMover9001, StackYard};
 
-/// The number of pre-allocated columns
-const NUMBER_OF_COLUMNS: usize = 9;
+/// The errors that can be triggered during Day 05
+#[derive(Debug)]
+enum Day05Error {
+    /// Failed to read the given input file
+    Io(std::io::Error),
 
-/// The max height of all columns
-const COLUMN_HEIGHT: usize = 42;
+    /// The input failed to parse
+    Parse(ParseError),
+
+    /// Invalid command line argument
+    InvalidArgument(String),
+
+    /// A move references a (1-indexed) column that isn't in the yard
+    UnknownColumn(usize),
+
+    /// A move takes more crates than the source stack holds. Holds the move and the
+    /// number of crates available.
+    NotEnoughCrates(Move, usize),
+}
+
+impl std::fmt::Display for Day05Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Day05Error::Io(err) => write!(f, "{err}"),
+            Day05Error::Parse(err) => write!(f, "{err}"),
+            Day05Error::InvalidArgument(arg) => write!(f, "invalid argument: {arg:?}"),
+            Day05Error::UnknownColumn(column) => write!(f, "column {column} doesn't exist"),
+            Day05Error::NotEnoughCrates(mv, available) => {
+                write!(f, "{mv}: only {available} crate(s) available")
+            }
+        }
+    }
+}
+
+// The dimensions of the input and the input itself, generated by `build.rs`:
+//
+// `NUMBER_OF_COLUMNS`: The number of columns in the drawing
+// `DRAWING_ROWS`: The number of crate rows in the drawing
+// `COLUMN_HEIGHT`: The max height of any column while executing the moves
+// `INPUT`: The input bytes (from `DAY05_INPUT` or `../input`)
+include!(concat!(env!("OUT_DIR"), "/dimensions.rs"));
 
 const fn parse_input_setup() -> (
     [[Option<u8>; COLUMN_HEIGHT]; NUMBER_OF_COLUMNS],
@@ -17,13 +52,16 @@ const fn parse_input_setup() -> (
     usize,
 ) {
     let mut input_index = 0;
-    let mut col_index = 0;
 
-    // Allocate 10 columns that can contain COLUMN_HEIGHT elements each
+    // The index of the first byte of the current line and the current row (from the top)
+    let mut line_start = 0;
+    let mut row = 0;
+
+    // Allocate the columns that can contain COLUMN_HEIGHT elements each
     let mut cols = [[None; COLUMN_HEIGHT]; NUMBER_OF_COLUMNS];
 
     // The current next index to insert an element for each column
-    let mut col_next_index = [COLUMN_HEIGHT - 1; NUMBER_OF_COLUMNS];
+    let mut col_next_index = [0; NUMBER_OF_COLUMNS];
 
     // Parse the initial input column state
     loop {
@@ -36,131 +74,100 @@ const fn parse_input_setup() -> (
         // Get the current byte of the index
         let curr_byte = INPUT[input_index];
 
-        if input_index % 4 == 1 {
-            if curr_byte.is_ascii_uppercase() {
-                let curr_col_next_index = &mut col_next_index[col_index];
-                cols[col_index][*curr_col_next_index] = Some(curr_byte);
-
-                // Decrement the next index of the current column since we are reading
-                // the columns from top to bottom
-                *curr_col_next_index -= 1;
-            }
-
-            // Increment the column index
-            col_index += 1;
-
-            if col_index > NUMBER_OF_COLUMNS {
-                panic!("Too many columns. Increment the NUMBER_OF_COLUMNS const variable");
+        // Each column is 4 bytes wide (`[A] `) with the letter at offset 1
+        let line_offset = input_index - line_start;
+        if line_offset % 4 == 1 && curr_byte.is_ascii_alphabetic() {
+            let col_index = line_offset / 4;
+            assert!(
+                col_index < NUMBER_OF_COLUMNS,
+                "Crate found after the last column"
+            );
+
+            // Rows are read from top to bottom, so the bottom row is index 0
+            let height = DRAWING_ROWS - (row + 1);
+            cols[col_index][height] = Some(curr_byte);
+
+            if col_next_index[col_index] < height + 1 {
+                col_next_index[col_index] = height + 1;
             }
         }
 
-        // If we hit a newline, reset the column index
+        // If we hit a newline, move to the next row
         if curr_byte == b'\n' {
-            col_index = 0;
+            line_start = input_index + 1;
+            row += 1;
         }
 
         // Increment the input index
         input_index += 1;
     }
 
-    // Currently, all of the values are at the end of each array. We want to move all
-    // of the values to the beginning of each array so that the bottom of each column is
-    // at index 0
-    let mut column = 0;
-    loop {
-        if column >= NUMBER_OF_COLUMNS {
-            break;
-        }
-
-        // +1 here since the col_next_index always points to the next index to write to.
-        let curr_col_index = col_next_index[column] + 1;
-
-        // Reset the column indexes to point to the index just past the top of each
-        // column after inversion
-        let new_col_index = COLUMN_HEIGHT - curr_col_index;
-        col_next_index[column] = new_col_index;
+    (cols, col_next_index, input_index)
+}
 
-        let mut index = curr_col_index;
-        loop {
-            // If the index is beyond the maximum height, we are finished with this
-            // column
-            if index >= COLUMN_HEIGHT {
-                break;
-            }
+/// Parse the decimal number starting at `input_index`, returning the number and the
+/// index just past it
+const fn parse_number(mut input_index: usize) -> (usize, usize) {
+    assert!(
+        INPUT[input_index].is_ascii_digit(),
+        "Number not found in instruction line"
+    );
+
+    let mut value = 0;
+    while input_index < INPUT.len() && INPUT[input_index].is_ascii_digit() {
+        value = value * 10 + (INPUT[input_index] - b'0') as usize;
+        input_index += 1;
+    }
 
-            // Take the value from the end of the array (leaving the None) and move it to
-            // the beginning of the array
-            let curr_value = cols[column][index].take();
-            cols[column][index - curr_col_index] = curr_value;
+    (value, input_index)
+}
 
-            // Increment to the next index
-            index += 1;
+/// Returns `true` if `word` is found in `INPUT` at `input_index`
+const fn starts_with(input_index: usize, word: &[u8]) -> bool {
+    let mut index = 0;
+    while index < word.len() {
+        if input_index + index >= INPUT.len() || INPUT[input_index + index] != word[index] {
+            return false;
         }
 
-        // Go to the next column
-        column += 1;
+        index += 1;
     }
 
-    (cols, col_next_index, input_index)
+    true
 }
 
-const fn part1() -> [char; NUMBER_OF_COLUMNS] {
+/// Execute every `move` instruction with the given crane model and return the top crate
+/// of each column. The CrateMover 9000 (`block_moves == false`) moves crates one at a
+/// time, the CrateMover 9001 (`block_moves == true`) moves them all at once.
+const fn solve(block_moves: bool) -> [char; NUMBER_OF_COLUMNS] {
     let (mut cols, mut col_next_index, mut input_index) = parse_input_setup();
 
     // Now that the columns are parsed and at the beginning of each array, we can begin
     // executing the `move` instructions
     loop {
+        // Skip any blank lines
+        while input_index < INPUT.len() && INPUT[input_index] == b'\n' {
+            input_index += 1;
+        }
+
         if input_index >= INPUT.len() {
             break;
         }
 
         // Sanity check we are at the beginning of each parsed line
         assert!(
-            INPUT[input_index] == b'm'
-                && INPUT[input_index + 1] == b'o'
-                && INPUT[input_index + 2] == b'v'
-                && INPUT[input_index + 3] == b'e'
-                && INPUT[input_index + 4] == b' ',
+            starts_with(input_index, b"move "),
             "`move ` not found at the beginning of the instruction"
         );
 
         // Increment past `move`
         input_index += 5;
 
-        let val1 = INPUT[input_index];
-
-        //
-        input_index += 1;
-
-        let val2 = INPUT[input_index];
-
-        // Parse the one or two digit count value
-        let mut count = match val2 {
-            b' ' => {
-                // Found a single digit count. Use val1
-                val1 - b'0'
-            }
-            b'0'..=b'9' => {
-                // Found a two digit count. Use val2 val1
-                input_index += 1;
-
-                let val2 = val2 - b'0';
-                let val1 = val1 - b'0';
-
-                val1 * 10 + val2
-            }
-            _ => {
-                panic!("Invalid `move` instruction line");
-            }
-        };
+        let (mut count, next_index) = parse_number(input_index);
+        input_index = next_index;
 
         assert!(
-            INPUT[input_index] == b' '
-                && INPUT[input_index + 1] == b'f'
-                && INPUT[input_index + 2] == b'r'
-                && INPUT[input_index + 3] == b'o'
-                && INPUT[input_index + 4] == b'm'
-                && INPUT[input_index + 5] == b' ',
+            starts_with(input_index, b" from "),
             "` from ` not found in instruction line"
         );
 
@@ -168,49 +175,88 @@ const fn part1() -> [char; NUMBER_OF_COLUMNS] {
         input_index += 6;
 
         // -1 here since all columns are "1-indexed in the puzzle"
-        let src_col = (INPUT[input_index] - b'0') as usize - 1;
-        input_index += 1 + " to ".len();
-        let dst_col = (INPUT[input_index] - b'0') as usize - 1;
+        let (src_col, next_index) = parse_number(input_index);
+        let src_col = src_col - 1;
+        input_index = next_index;
 
-        loop {
-            if count == 0 {
-                break;
-            }
+        assert!(
+            starts_with(input_index, b" to "),
+            "` to ` not found in instruction line"
+        );
 
-            // Take the value from the source column and put it into the dest column
-            col_next_index[src_col] -= 1;
-            let src_col_height = col_next_index[src_col];
-            let value = cols[src_col][src_col_height].take();
-            assert!(value.is_some(), "Attempted to move a None value");
+        // Increment past the ` to `
+        input_index += 4;
 
-            let dst_col_height = col_next_index[dst_col];
-            col_next_index[dst_col] += 1;
-            cols[dst_col][dst_col_height] = value;
+        let (dst_col, next_index) = parse_number(input_index);
+        let dst_col = dst_col - 1;
+        input_index = next_index;
 
-            count -= 1;
+        // Moving crates onto the same column doesn't change anything for either crane
+        if src_col == dst_col {
+            count = 0;
         }
 
-        // Increment past the dst_col
-        input_index += 1;
+        if block_moves {
+            let init_count = count;
+            loop {
+                if count == 0 {
+                    break;
+                }
+
+                // Take the value from the source column and put it into the dest column,
+                // keeping the order of the moved block
+                let src_col_height = col_next_index[src_col] - count;
 
+                let dst_col_height = col_next_index[dst_col];
+                col_next_index[dst_col] += 1;
+
+                let value = cols[src_col][src_col_height].take();
+                assert!(value.is_some(), "Attempted to move a None value");
+                cols[dst_col][dst_col_height] = value;
+
+                count -= 1;
+            }
+
+            // We can now adjust the source index now that the copy has finished
+            col_next_index[src_col] -= init_count;
+        } else {
+            loop {
+                if count == 0 {
+                    break;
+                }
+
+                // Take the value from the source column and put it into the dest column
+                col_next_index[src_col] -= 1;
+                let src_col_height = col_next_index[src_col];
+                let value = cols[src_col][src_col_height].take();
+                assert!(value.is_some(), "Attempted to move a None value");
+
+                let dst_col_height = col_next_index[dst_col];
+                col_next_index[dst_col] += 1;
+                cols[dst_col][dst_col_height] = value;
+
+                count -= 1;
+            }
+        }
+
+        // Sanity check we made it to the end of the line
         assert!(
-            INPUT[input_index] == b'\n',
+            input_index >= INPUT.len() || INPUT[input_index] == b'\n',
             "Newline not found at the end of the line"
         );
-
-        // Increment past the newline
-        input_index += 1;
     }
 
     let mut col_index = 0;
-    let mut solution = ['?'; NUMBER_OF_COLUMNS];
+    let mut solution = [' '; NUMBER_OF_COLUMNS];
     loop {
         if col_index >= NUMBER_OF_COLUMNS {
             break;
         }
 
-        let curr_height = col_next_index[col_index] - 1;
-        solution[col_index] = cols[col_index][curr_height].unwrap() as char;
+        // Empty columns keep a space as their top crate
+        if let Some(curr_height) = col_next_index[col_index].checked_sub(1) {
+            solution[col_index] = cols[col_index][curr_height].unwrap() as char;
+        }
 
         col_index += 1;
     }
@@ -218,128 +264,119 @@ const fn part1() -> [char; NUMBER_OF_COLUMNS] {
     solution
 }
 
+/// Solve part 1 with the CrateMover 9000
+const fn part1() -> [char; NUMBER_OF_COLUMNS] {
+    solve(false)
+}
+
+/// Solve part 2 with the CrateMover 9001
 const fn part2() -> [char; NUMBER_OF_COLUMNS] {
-    let (mut cols, mut col_next_index, mut input_index) = parse_input_setup();
+    solve(true)
+}
 
-    // Now that the columns are parsed and at the beginning of each array, we can begin
-    // executing the `move` instructions
-    loop {
-        if input_index >= INPUT.len() {
-            break;
-        }
+/// The command line options for the runtime solver
+#[derive(Debug, Default)]
+struct Options {
+    /// Stop after this (1-indexed) instruction
+    stop: Option<usize>,
 
-        // Sanity check we are at the beginning of each parsed line
-        assert!(
-            INPUT[input_index] == b'm'
-                && INPUT[input_index + 1] == b'o'
-                && INPUT[input_index + 2] == b'v'
-                && INPUT[input_index + 3] == b'e'
-                && INPUT[input_index + 4] == b' ',
-            "`move ` not found at the beginning of the instruction"
-        );
+    /// Print the top crates after each move
+    trace: bool,
 
-        // Increment past `move`
-        input_index += 5;
+    /// Print the drawing of the yard before and after each move
+    dump: bool,
 
-        let val1 = INPUT[input_index];
+    /// Print the drawing of the yard after the last move
+    print: bool,
+}
 
-        //
-        input_index += 1;
+/// Run `moves` on a new yard operated by `crane`, stopping early if requested, and
+/// print the top crates
+fn run_crane<C: Crane + Clone>(
+    crane: C,
+    stacks: &[Vec<u8>],
+    moves: &[Move],
+    options: &Options,
+) -> Result<(), Day05Error> {
+    let mut yard = StackYard::new(crane, stacks.to_vec());
+    match options.stop {
+        Some(n) => yard.run_until(moves, n)?,
+        None => yard.run(moves)?,
+    }
 
-        let val2 = INPUT[input_index];
+    if options.dump {
+        println!("{}\n{}", C::NAME, render_drawing(stacks));
+    }
 
-        // Parse the one or two digit count value
-        let mut count = match val2 {
-            b' ' => {
-                // Found a single digit count. Use val1
-                val1 - b'0'
+    if options.trace || options.dump {
+        yard.replay(|index, mv, stacks| {
+            if options.trace {
+                println!("{index:4} {mv:22} {}", yard::top_crates(stacks));
             }
-            b'0'..=b'9' => {
-                // Found a two digit count. Use val2 val1
-                input_index += 1;
-
-                let val2 = val2 - b'0';
-                let val1 = val1 - b'0';
 
-                val1 * 10 + val2
+            if options.dump {
+                println!("{index}: {mv}\n{}", render_drawing(stacks));
             }
-            _ => {
-                panic!("Invalid `move` instruction line");
-            }
-        };
-
-        assert!(
-            INPUT[input_index] == b' '
-                && INPUT[input_index + 1] == b'f'
-                && INPUT[input_index + 2] == b'r'
-                && INPUT[input_index + 3] == b'o'
-                && INPUT[input_index + 4] == b'm'
-                && INPUT[input_index + 5] == b' ',
-            "` from ` not found in instruction line"
-        );
-
-        // Increment past the ` from `
-        input_index += 6;
+        });
+    }
 
-        // -1 here since all columns are "1-indexed in the puzzle"
-        let src_col = (INPUT[input_index] - b'0') as usize - 1;
-        input_index += 1 + " to ".len();
-        let dst_col = (INPUT[input_index] - b'0') as usize - 1;
-
-        let init_count = count;
-        loop {
-            if count == 0 {
-                break;
-            }
+    if options.print {
+        print!("{yard}");
+    }
 
-            // Take the value from the source column and put it into the dest column
-            let src_col_height = col_next_index[src_col] - count as usize;
+    println!(
+        "{} after {} moves: {}",
+        C::NAME,
+        yard.history().len(),
+        yard.top_crates()
+    );
+
+    // Sanity check the recorded history by undoing every move
+    while yard.undo().is_some() {}
+    assert_eq!(
+        yard.stacks(),
+        stacks,
+        "Undoing every move didn't restore the initial stacks"
+    );
+
+    Ok(())
+}
 
-            let dst_col_height = col_next_index[dst_col];
-            col_next_index[dst_col] += 1;
+fn main() -> Result<(), Day05Error> {
+    const PART1_SOLUTION: [char; NUMBER_OF_COLUMNS] = part1();
+    println!("{PART1_SOLUTION:?}");
 
-            let value = cols[src_col][src_col_height].take();
-            cols[dst_col][dst_col_height] = value;
+    const PART2_SOLUTION: [char; NUMBER_OF_COLUMNS] = part2();
+    println!("{PART2_SOLUTION:?}");
 
-            count -= 1;
+    // Usage: day05 [INPUT] [--stop N] [--trace] [--dump] [--print]
+    let mut input_path = None;
+    let mut options = Options::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--stop" => {
+                let n = args.next().and_then(|n| n.parse::<usize>().ok());
+                options.stop = Some(n.ok_or(Day05Error::InvalidArgument(arg))?);
+            }
+            "--trace" => options.trace = true,
+            "--dump" => options.dump = true,
+            "--print" => options.print = true,
+            _ => input_path = Some(arg),
         }
-
-        // We can now adjust the source index now that the copy has finished
-        col_next_index[src_col] -= init_count as usize;
-
-        // Increment past the dst_col
-        input_index += 1;
-
-        // Sanity check we made it to the end of the line
-        assert!(
-            INPUT[input_index] == b'\n',
-            "Newline not found at the end of the line"
-        );
-
-        // Increment past the newline
-        input_index += 1;
     }
 
-    let mut col_index = 0;
-    let mut solution = ['?'; NUMBER_OF_COLUMNS];
-    loop {
-        if col_index >= NUMBER_OF_COLUMNS {
-            break;
-        }
-
-        let curr_height = col_next_index[col_index] - 1;
-        solution[col_index] = cols[col_index][curr_height].unwrap() as char;
+    // The compile time solutions only work for the input given at build time. Parse the
+    // input file given on the command line (or the embedded input) at runtime.
+    let input = match input_path {
+        Some(path) => std::fs::read_to_string(path).map_err(Day05Error::Io)?,
+        None => String::from_utf8_lossy(INPUT).into_owned(),
+    };
 
-        col_index += 1;
-    }
+    let (stacks, moves) = parse::parse_input(&input).map_err(Day05Error::Parse)?;
 
-    solution
-}
+    run_crane(CrateMover9000, &stacks, &moves, &options)?;
+    run_crane(CrateMover9001, &stacks, &moves, &options)?;
 
-fn main() {
-    const PART1_SOLUTION: [char; 9] = part1();
-    println!("{PART1_SOLUTION:?}");
-
-    const PART2_SOLUTION: [char; 9] = part2();
-    println!("{PART2_SOLUTION:?}");
+    Ok(())
 }
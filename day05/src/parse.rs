@@ -0,0 +1,227 @@
+//! Runtime parser for the crate drawing and the `move` instructions
+//!
+//! Unlike the `const fn` parser, this handles any number of columns, multi-digit
+//! column numbers and counts, and stacks of any height.
+
+/// The errors that can be triggered while parsing the Day 05 input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The empty line between the drawing and the instructions was not found
+    MissingSeparator,
+
+    /// The drawing doesn't end with a line of column numbers
+    MissingColumnNumbers,
+
+    /// The column number footer doesn't count up from 1. Holds the (1-indexed) column
+    /// and the label found there.
+    InvalidColumnNumber(usize, String),
+
+    /// A drawing cell isn't a crate (`[A]`) or empty (`   `). Holds the (1-indexed)
+    /// line and column.
+    InvalidCrate(usize, usize),
+
+    /// A crate was drawn to the right of the last numbered column. Holds the
+    /// (1-indexed) line.
+    CrateOutsideColumns(usize),
+
+    /// A crate is floating above an empty space. Holds the (1-indexed) line and column.
+    FloatingCrate(usize, usize),
+
+    /// An instruction isn't of the form `move N from A to B`. Holds the (1-indexed)
+    /// line.
+    InvalidMove(usize),
+
+    /// A number in an instruction failed to parse. Holds the (1-indexed) line.
+    ParseIntError(usize, std::num::ParseIntError),
+
+    /// An instruction references a column that isn't in the drawing. Holds the
+    /// (1-indexed) line and the column number.
+    ColumnOutOfRange(usize, usize),
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseError::MissingSeparator => {
+                write!(f, "no empty line between the drawing and the instructions")
+            }
+            ParseError::MissingColumnNumbers => {
+                write!(f, "the drawing doesn't end with the column numbers")
+            }
+            ParseError::InvalidColumnNumber(column, label) => {
+                write!(f, "expected column number {column}, found {label:?}")
+            }
+            ParseError::InvalidCrate(line, column) => {
+                write!(f, "line {line}: invalid crate in column {column}")
+            }
+            ParseError::CrateOutsideColumns(line) => {
+                write!(f, "line {line}: crate found after the last column")
+            }
+            ParseError::FloatingCrate(line, column) => {
+                write!(
+                    f,
+                    "line {line}: crate in column {column} has nothing under it"
+                )
+            }
+            ParseError::InvalidMove(line) => {
+                write!(f, "line {line}: expected `move N from A to B`")
+            }
+            ParseError::ParseIntError(line, err) => write!(f, "line {line}: {err}"),
+            ParseError::ColumnOutOfRange(line, column) => {
+                write!(f, "line {line}: column {column} is not in the drawing")
+            }
+        }
+    }
+}
+
+/// A single `move N from A to B` instruction
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Move {
+    /// The number of crates to move
+    pub count: usize,
+
+    /// The (0-indexed) column to take the crates from
+    pub src: usize,
+
+    /// The (0-indexed) column to put the crates on
+    pub dst: usize,
+}
+
+impl std::fmt::Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        // Columns are 1-indexed in the puzzle
+        write!(
+            f,
+            "move {} from {} to {}",
+            self.count,
+            self.src + 1,
+            self.dst + 1
+        )
+    }
+}
+
+/// Parse the full puzzle input into the stacks (bottom crate first) and instructions
+pub fn parse_input(input: &str) -> Result<(Vec<Vec<u8>>, Vec<Move>), ParseError> {
+    let (drawing, moves) = input
+        .split_once("\n\n")
+        .ok_or(ParseError::MissingSeparator)?;
+
+    let stacks = parse_drawing(drawing)?;
+
+    // The instructions start after the drawing and the empty line
+    let first_line = drawing.lines().count() + 2;
+    let moves = parse_moves(moves, first_line, stacks.len())?;
+
+    Ok((stacks, moves))
+}
+
+/// Parse the crate drawing into the stacks of each column, bottom crate first
+///
+/// ```text
+///     [D]
+/// [N] [C]
+/// [Z] [M] [P]
+///  1   2   3
+/// ```
+pub fn parse_drawing(drawing: &str) -> Result<Vec<Vec<u8>>, ParseError> {
+    let mut lines = drawing.lines().collect::<Vec<_>>();
+
+    // The last line of the drawing numbers each column
+    let footer = lines.pop().ok_or(ParseError::MissingColumnNumbers)?;
+    let mut number_of_columns = 0;
+    for (index, label) in footer.split_whitespace().enumerate() {
+        let column = index + 1;
+        if label.parse::<usize>() != Ok(column) {
+            return Err(ParseError::InvalidColumnNumber(column, label.to_string()));
+        }
+
+        number_of_columns = column;
+    }
+
+    if number_of_columns == 0 {
+        return Err(ParseError::MissingColumnNumbers);
+    }
+
+    let mut stacks = vec![Vec::new(); number_of_columns];
+
+    // Read the crate rows from the bottom up so each stack is built bottom first
+    for (line_index, line) in lines.iter().enumerate().rev() {
+        let line_number = line_index + 1;
+        let line = line.as_bytes();
+
+        // Each column is 4 bytes wide: `[A] `
+        for (column, cell) in line.chunks(4).enumerate() {
+            let letter = match cell {
+                [b'[', letter, b']'] | [b'[', letter, b']', b' ']
+                    if letter.is_ascii_alphabetic() =>
+                {
+                    *letter
+                }
+                [b' ', b' ', b' ', b' '] | [b' ', b' ', b' '] | [b' ', b' '] | [b' '] => continue,
+                _ => return Err(ParseError::InvalidCrate(line_number, column + 1)),
+            };
+
+            let stack = stacks
+                .get_mut(column)
+                .ok_or(ParseError::CrateOutsideColumns(line_number))?;
+
+            // Every crate below this row should already be in the stack
+            if stack.len() != lines.len() - 1 - line_index {
+                return Err(ParseError::FloatingCrate(line_number, column + 1));
+            }
+
+            stack.push(letter);
+        }
+    }
+
+    Ok(stacks)
+}
+
+/// Parse the `move N from A to B` instructions for a drawing of `number_of_columns`
+/// columns. `first_line` is the line number of the first instruction for errors.
+pub fn parse_moves(
+    moves: &str,
+    first_line: usize,
+    number_of_columns: usize,
+) -> Result<Vec<Move>, ParseError> {
+    let mut result = Vec::new();
+
+    for (index, line) in moves.lines().enumerate() {
+        let line_number = first_line + index;
+
+        // Ignore blank lines at the end of the input
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let ["move", count, "from", src, "to", dst] =
+            line.split_whitespace().collect::<Vec<_>>()[..]
+        else {
+            return Err(ParseError::InvalidMove(line_number));
+        };
+
+        let parse = |value: &str| {
+            value
+                .parse::<usize>()
+                .map_err(|err| ParseError::ParseIntError(line_number, err))
+        };
+
+        // Columns are 1-indexed in the puzzle
+        let column = |value: &str| {
+            let column = parse(value)?;
+            if column == 0 || column > number_of_columns {
+                return Err(ParseError::ColumnOutOfRange(line_number, column));
+            }
+
+            Ok(column - 1)
+        };
+
+        result.push(Move {
+            count: parse(count)?,
+            src: column(src)?,
+            dst: column(dst)?,
+        });
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,214 @@
+//! The crate stacks and the crane models that rearrange them
+
+use crate::parse::Move;
+use crate::Day05Error;
+
+/// A crane model that knows how to execute a single `move` instruction
+pub trait Crane {
+    /// The model name of the crane
+    const NAME: &'static str;
+
+    /// Move `mv.count` crates from the `mv.src` stack to the `mv.dst` stack. The caller
+    /// guarantees the source stack holds at least `mv.count` crates.
+    ///
+    /// Every crane's move must be undone by the same crane moving the crates back from
+    /// `mv.dst` to `mv.src`.
+    fn apply(&self, stacks: &mut [Vec<u8>], mv: Move);
+}
+
+/// The CrateMover 9000 moves crates one at a time, reversing their order
+#[derive(Debug, Copy, Clone, Default)]
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    const NAME: &'static str = "CrateMover 9000";
+
+    fn apply(&self, stacks: &mut [Vec<u8>], mv: Move) {
+        for _ in 0..mv.count {
+            let value = stacks[mv.src]
+                .pop()
+                .expect("Source stack checked by caller");
+            stacks[mv.dst].push(value);
+        }
+    }
+}
+
+/// The CrateMover 9001 moves all crates at once, keeping their order
+#[derive(Debug, Copy, Clone, Default)]
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    const NAME: &'static str = "CrateMover 9001";
+
+    fn apply(&self, stacks: &mut [Vec<u8>], mv: Move) {
+        let src_height = stacks[mv.src].len() - mv.count;
+        let block = stacks[mv.src].split_off(src_height);
+        stacks[mv.dst].extend(block);
+    }
+}
+
+/// The crate stacks along with every move applied to them by a given crane
+#[derive(Debug, Clone)]
+pub struct StackYard<C: Crane> {
+    /// The crane executing the moves
+    crane: C,
+
+    /// The stacks before any move was applied, bottom crate first
+    initial: Vec<Vec<u8>>,
+
+    /// The current stacks, bottom crate first
+    stacks: Vec<Vec<u8>>,
+
+    /// Every move applied so far, in order
+    history: Vec<Move>,
+}
+
+impl<C: Crane> StackYard<C> {
+    /// Create a yard operated by `crane` from the given stacks (bottom crate first)
+    pub fn new(crane: C, stacks: Vec<Vec<u8>>) -> Self {
+        Self {
+            crane,
+            initial: stacks.clone(),
+            stacks,
+            history: Vec::new(),
+        }
+    }
+
+    /// The current stacks, bottom crate first
+    pub fn stacks(&self) -> &[Vec<u8>] {
+        &self.stacks
+    }
+
+    /// Every move applied so far, in order
+    pub fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    /// Apply a single move and record it in the history
+    pub fn apply(&mut self, mv: Move) -> Result<(), Day05Error> {
+        let available = self
+            .stacks
+            .get(mv.src)
+            .ok_or(Day05Error::UnknownColumn(mv.src + 1))?
+            .len();
+
+        if mv.dst >= self.stacks.len() {
+            return Err(Day05Error::UnknownColumn(mv.dst + 1));
+        }
+
+        if available < mv.count {
+            return Err(Day05Error::NotEnoughCrates(mv, available));
+        }
+
+        self.crane.apply(&mut self.stacks, mv);
+        self.history.push(mv);
+
+        Ok(())
+    }
+
+    /// Apply at most the first `n` of `moves`, stopping at instruction `n`
+    pub fn run_until(&mut self, moves: &[Move], n: usize) -> Result<(), Day05Error> {
+        for mv in moves.iter().take(n) {
+            self.apply(*mv)?;
+        }
+
+        Ok(())
+    }
+
+    /// Apply all of `moves`
+    pub fn run(&mut self, moves: &[Move]) -> Result<(), Day05Error> {
+        self.run_until(moves, moves.len())
+    }
+
+    /// Undo the most recently applied move, returning it
+    pub fn undo(&mut self) -> Option<Move> {
+        let mv = self.history.pop()?;
+
+        // Moving the crates back with the same crane restores the previous state
+        let reverse = Move {
+            count: mv.count,
+            src: mv.dst,
+            dst: mv.src,
+        };
+        self.crane.apply(&mut self.stacks, reverse);
+
+        Some(mv)
+    }
+
+    /// Replay the recorded history from the initial stacks, calling `f` with the
+    /// (1-indexed) instruction number, the move, and the stacks after each move
+    pub fn replay(&self, mut f: impl FnMut(usize, Move, &[Vec<u8>]))
+    where
+        C: Clone,
+    {
+        let mut yard = StackYard::new(self.crane.clone(), self.initial.clone());
+
+        for (index, mv) in self.history.iter().enumerate() {
+            yard.apply(*mv)
+                .expect("Recorded moves were already applied once");
+            f(index + 1, *mv, yard.stacks());
+        }
+    }
+
+    /// The top crate of each stack, with a space for empty stacks
+    pub fn top_crates(&self) -> String {
+        top_crates(&self.stacks)
+    }
+}
+
+impl<C: Crane> std::fmt::Display for StackYard<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", render_drawing(&self.stacks))
+    }
+}
+
+/// Render the `stacks` (bottom crate first) in the puzzle's drawing format, including
+/// the column number footer. Every line is padded to the full width of the drawing, so
+/// rendering a parsed drawing gives back the same bytes.
+///
+/// ```text
+///     [D]    
+/// [N] [C]    
+/// [Z] [M] [P]
+///  1   2   3
+/// ```
+pub fn render_drawing(stacks: &[Vec<u8>]) -> String {
+    let mut drawing = String::new();
+
+    // Draw the rows from the top of the tallest stack down to the bottom
+    let height = stacks.iter().map(Vec::len).max().unwrap_or(0);
+    for row in (0..height).rev() {
+        let line = stacks
+            .iter()
+            .map(|stack| {
+                stack.get(row).map_or_else(
+                    || "   ".to_string(),
+                    |value| format!("[{}]", *value as char),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        drawing.push_str(&line);
+        drawing.push('\n');
+    }
+
+    // Center the (1-indexed) column numbers under each column
+    let footer = (1..=stacks.len())
+        .map(|column| format!("{column:^3}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    drawing.push_str(&footer);
+    drawing.push('\n');
+
+    drawing
+}
+
+/// The top crate of each of the `stacks`, with a space for empty stacks
+pub fn top_crates(stacks: &[Vec<u8>]) -> String {
+    stacks
+        .iter()
+        .map(|stack| stack.last().map_or(' ', |value| *value as char))
+        .collect()
+}
@@ -0,0 +1,195 @@
+//! Framing of a datastream into packets and messages using its markers
+//!
+//! The decoder skips the stream until the first start-of-packet marker. The bytes
+//! after it, up to the first start-of-message marker, are the packet. Each
+//! start-of-message marker then starts a message running up to the next
+//! start-of-message marker or the end of the stream. Markers never overlap: the search
+//! for the next marker starts after the end of the previous one.
+
+use std::io::{BufReader, Bytes, Read};
+
+use crate::marker::MarkerWindow;
+
+/// The default length of the start-of-packet marker
+pub const PACKET_MARKER_LEN: usize = 4;
+
+/// The default length of the start-of-message marker
+pub const MESSAGE_MARKER_LEN: usize = 14;
+
+/// The kind of a decoded frame
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FrameKind {
+    /// The bytes between the start-of-packet marker and the first message
+    Packet,
+
+    /// The bytes following a start-of-message marker
+    Message,
+}
+
+impl FrameKind {
+    /// The lowercase name of the kind
+    pub fn name(self) -> &'static str {
+        match self {
+            FrameKind::Packet => "packet",
+            FrameKind::Message => "message",
+        }
+    }
+}
+
+/// A single frame decoded from the datastream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// The kind of marker that started this frame
+    pub kind: FrameKind,
+
+    /// The stream offset of the marker that started this frame
+    pub marker_offset: usize,
+
+    /// The stream offset of the first payload byte
+    pub offset: usize,
+
+    /// The bytes of the frame, not including any marker
+    pub payload: Vec<u8>,
+}
+
+impl Frame {
+    /// Write the payload as lowercase hex
+    pub fn hex(&self) -> String {
+        self.payload
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    /// Write the frame as a single line of JSON, with the payload as hex
+    pub fn json(&self) -> String {
+        format!(
+            r#"{{"kind":"{}","marker_offset":{},"offset":{},"length":{},"payload":"{}"}}"#,
+            self.kind.name(),
+            self.marker_offset,
+            self.offset,
+            self.payload.len(),
+            self.hex()
+        )
+    }
+}
+
+/// The state of the decoder
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum State {
+    /// Skipping bytes until the start-of-packet marker
+    Sync,
+
+    /// Collecting the bytes of a frame of the given kind
+    Frame(FrameKind),
+
+    /// The stream has ended
+    Done,
+}
+
+/// Streaming decoder splitting a datastream into framed packets and messages
+pub struct Decoder<R: Read> {
+    /// The source of the datastream
+    bytes: Bytes<BufReader<R>>,
+
+    /// The window searching for the start-of-packet marker
+    packet_window: MarkerWindow,
+
+    /// The window searching for the start-of-message marker
+    message_window: MarkerWindow,
+
+    /// The current state of the decoder
+    state: State,
+
+    /// The number of bytes read so far
+    position: usize,
+
+    /// The stream offset of the marker that started the current frame
+    marker_offset: usize,
+
+    /// The stream offset of the first payload byte of the current frame
+    offset: usize,
+
+    /// The bytes of the current frame read so far
+    payload: Vec<u8>,
+}
+
+impl<R: Read> Decoder<R> {
+    /// Create a decoder for `reader` with the given start-of-packet and
+    /// start-of-message marker lengths
+    pub fn new(reader: R, packet_len: usize, message_len: usize) -> Self {
+        Self {
+            bytes: BufReader::new(reader).bytes(),
+            packet_window: MarkerWindow::new(packet_len),
+            message_window: MarkerWindow::new(message_len),
+            state: State::Sync,
+            position: 0,
+            marker_offset: 0,
+            offset: 0,
+            payload: Vec::new(),
+        }
+    }
+
+    /// Finish the current frame of `kind`
+    fn take_frame(&mut self, kind: FrameKind) -> Frame {
+        Frame {
+            kind,
+            marker_offset: self.marker_offset,
+            offset: self.offset,
+            payload: std::mem::take(&mut self.payload),
+        }
+    }
+}
+
+impl<R: Read> Iterator for Decoder<R> {
+    type Item = std::io::Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let byte = match self.bytes.next() {
+                Some(Ok(byte)) => byte,
+                Some(Err(err)) => return Some(Err(err)),
+                None => {
+                    // Emit the frame still being collected at the end of the stream
+                    return match std::mem::replace(&mut self.state, State::Done) {
+                        State::Frame(kind) => Some(Ok(self.take_frame(kind))),
+                        State::Sync | State::Done => None,
+                    };
+                }
+            };
+
+            self.position += 1;
+
+            match self.state {
+                State::Done => return None,
+                State::Sync => {
+                    // Bytes before the start-of-packet marker are not part of any frame
+                    if self.packet_window.push(byte) {
+                        self.marker_offset = self.position - self.packet_window.len();
+                        self.offset = self.position;
+                        self.state = State::Frame(FrameKind::Packet);
+                    }
+                }
+                State::Frame(kind) => {
+                    self.payload.push(byte);
+
+                    if self.message_window.push(byte) {
+                        // The end of the payload is the start-of-message marker ending
+                        // the current frame
+                        let marker_len = self.message_window.len();
+                        self.payload.truncate(self.payload.len() - marker_len);
+                        let frame = self.take_frame(kind);
+
+                        // Start the message after the marker
+                        self.message_window.reset();
+                        self.marker_offset = self.position - marker_len;
+                        self.offset = self.position;
+                        self.state = State::Frame(FrameKind::Message);
+
+                        return Some(Ok(frame));
+                    }
+                }
+            }
+        }
+    }
+}
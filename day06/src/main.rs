@@ -1,45 +1,180 @@
+mod frame;
+mod marker;
+
+use std::io::{BufReader, Read};
+
+use frame::{Decoder, MESSAGE_MARKER_LEN, PACKET_MARKER_LEN};
+use marker::{MarkerDetector, MarkerWindow};
+
 const INPUT: &[u8] = include_bytes!("../input");
 
-/// Find the starting character of the first unique `n` bytes in `INPUT`
-fn find_n_unique_bytes(n: usize, input: &[u8]) -> usize {
-    'next_chunk: for curr_index in 0..(input.len().saturating_sub(n)) {
-        let mut seen = 0u64;
-        for letter_index in 0..n {
-            // Get the value of the
-            let val = INPUT[curr_index + letter_index] - b'a';
-
-            // Check if this letter has already been seen. If so, break out to the loop
-            // to progress to the next chunk.
-            if seen & (1 << val) > 0 {
-                continue 'next_chunk;
-            }
+/// The errors that can be triggered during Day 06
+#[derive(Debug)]
+enum Day06Error {
+    /// Failed to read the datastream
+    Io(std::io::Error),
+
+    /// Invalid command line argument
+    InvalidArgument(String),
+}
 
-            // Newly seen number, mark its bit
-            seen |= 1 << val;
+impl std::fmt::Display for Day06Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Day06Error::Io(err) => write!(f, "{err}"),
+            Day06Error::InvalidArgument(arg) => write!(f, "invalid argument: {arg:?}"),
         }
+    }
+}
+
+/// The output format of the decoded frames
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Format {
+    /// One line per frame with the payload as hex
+    Hex,
+
+    /// One JSON object per line
+    Json,
+}
+
+/// The command line options
+struct Options {
+    /// The datastream to read: a file path, `-` for stdin, or the puzzle input
+    path: Option<String>,
+
+    /// Decode the datastream into frames instead of listing the markers
+    frames: bool,
+
+    /// The length of the start-of-packet marker
+    packet_len: usize,
+
+    /// The length of the start-of-message marker
+    message_len: usize,
+
+    /// The output format of the frames
+    format: Format,
+}
+
+impl Options {
+    /// Parse the command line:
+    ///
+    /// `day06 [FILE|-] [--frames] [--packet N] [--message N] [--format hex|json]`
+    fn parse() -> Result<Self, Day06Error> {
+        let mut options = Options {
+            path: None,
+            frames: false,
+            packet_len: PACKET_MARKER_LEN,
+            message_len: MESSAGE_MARKER_LEN,
+            format: Format::Hex,
+        };
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--frames" => options.frames = true,
+                "--packet" | "--message" => {
+                    let len = args
+                        .next()
+                        .and_then(|len| len.parse::<usize>().ok())
+                        .filter(|len| *len > 0)
+                        .ok_or_else(|| Day06Error::InvalidArgument(arg.clone()))?;
 
-        return curr_index;
+                    if arg == "--packet" {
+                        options.packet_len = len;
+                    } else {
+                        options.message_len = len;
+                    }
+                }
+                "--format" => {
+                    options.format = match args.next().as_deref() {
+                        Some("hex") => Format::Hex,
+                        Some("json") => Format::Json,
+                        _ => return Err(Day06Error::InvalidArgument(arg)),
+                    };
+                }
+                _ => options.path = Some(arg),
+            }
+        }
+
+        Ok(options)
     }
 
-    panic!("Failed to find unique {n} bytes");
+    /// Open the datastream to read
+    fn reader(&self) -> Result<Box<dyn Read>, Day06Error> {
+        Ok(match self.path.as_deref() {
+            None => Box::new(INPUT),
+            Some("-") => Box::new(std::io::stdin().lock()),
+            Some(path) => Box::new(std::fs::File::open(path).map_err(Day06Error::Io)?),
+        })
+    }
 }
 
-fn main() {
-    let part1 = find_n_unique_bytes(4, INPUT);
+fn main() -> Result<(), Day06Error> {
+    let options = Options::parse()?;
+
+    if options.frames {
+        for frame in Decoder::new(options.reader()?, options.packet_len, options.message_len) {
+            let frame = frame.map_err(Day06Error::Io)?;
+            match options.format {
+                Format::Hex => println!(
+                    "{:7} marker@{} payload@{} len {}: {}",
+                    frame.kind.name(),
+                    frame.marker_offset,
+                    frame.offset,
+                    frame.payload.len(),
+                    frame.hex()
+                ),
+                Format::Json => println!("{}", frame.json()),
+            }
+        }
+
+        return Ok(());
+    }
+
+    // List every marker of any other datastream given on the command line
+    if let Some(path) = &options.path {
+        // Drive both windows from a single pass, as stdin can only be read once
+        let mut windows = [options.packet_len, options.message_len].map(MarkerWindow::new);
+        let mut markers = [Vec::new(), Vec::new()];
+        for (index, byte) in BufReader::new(options.reader()?).bytes().enumerate() {
+            let byte = byte.map_err(Day06Error::Io)?;
+            for (window, markers) in windows.iter_mut().zip(markers.iter_mut()) {
+                if window.push(byte) {
+                    markers.push(index + 1);
+                }
+            }
+        }
+
+        for (window, markers) in windows.iter().zip(markers) {
+            println!("{} byte markers in {path}: {markers:?}", window.len());
+        }
+
+        return Ok(());
+    }
+
+    let part1 = MarkerDetector::new(INPUT, 4)
+        .next()
+        .expect("Failed to find unique 4 bytes")
+        .map_err(Day06Error::Io)?;
 
     // Found a set of 4 unique letters
     println!(
         "Part1 {}: curr_index: {:?}",
-        part1 + 4,
-        &INPUT[part1..part1 + 4]
+        part1,
+        &INPUT[part1 - 4..part1]
     );
 
-    let part2 = find_n_unique_bytes(14, INPUT);
+    let part2 = MarkerDetector::new(INPUT, 14)
+        .next()
+        .expect("Failed to find unique 14 bytes")
+        .map_err(Day06Error::Io)?;
 
     // Found a set of 14 unique letters
     println!(
         "Part2 {}: curr_index: {:?}",
-        part2 + 14,
-        &INPUT[part2..part2 + 14]
+        part2,
+        &INPUT[part2 - 14..part2]
     );
+
+    Ok(())
 }
@@ -0,0 +1,121 @@
+//! Detection of markers: runs of `n` bytes that are all different
+
+use std::io::{BufReader, Bytes, Read};
+
+/// The last `n` bytes of a datastream, with a count of each byte value so a new byte
+/// can be checked without rescanning the window
+pub struct MarkerWindow {
+    /// The number of unique bytes making up a marker
+    n: usize,
+
+    /// The last `n` bytes pushed, used as a ring buffer indexed by `pushed % n`
+    window: Vec<u8>,
+
+    /// The number of times each byte value is found in the window
+    counts: [usize; 256],
+
+    /// The number of byte values found more than once in the window
+    duplicates: usize,
+
+    /// The number of bytes pushed since the window was created or reset
+    pushed: usize,
+}
+
+impl MarkerWindow {
+    /// Create an empty window for markers of `n` unique bytes
+    pub fn new(n: usize) -> Self {
+        assert!(n > 0, "A marker needs at least one byte");
+
+        Self {
+            n,
+            window: vec![0; n],
+            counts: [0; 256],
+            duplicates: 0,
+            pushed: 0,
+        }
+    }
+
+    /// The number of unique bytes making up a marker
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Forget every byte pushed so far, so the next marker can't overlap earlier bytes
+    pub fn reset(&mut self) {
+        self.counts = [0; 256];
+        self.duplicates = 0;
+        self.pushed = 0;
+    }
+
+    /// Add `byte` to the window, returning `true` if it completes a marker
+    pub fn push(&mut self, byte: u8) -> bool {
+        let slot = self.pushed % self.n;
+
+        // Remove the byte leaving the window once it is full
+        if self.pushed >= self.n {
+            let old = &mut self.counts[self.window[slot] as usize];
+            if *old == 2 {
+                self.duplicates -= 1;
+            }
+            *old -= 1;
+        }
+
+        // Add the new byte to the window
+        let new = &mut self.counts[byte as usize];
+        *new += 1;
+        if *new == 2 {
+            self.duplicates += 1;
+        }
+
+        self.window[slot] = byte;
+        self.pushed += 1;
+
+        // A full window without duplicates is a marker
+        self.pushed >= self.n && self.duplicates == 0
+    }
+}
+
+/// Streaming detector yielding every marker in a datastream
+///
+/// Each byte of the source is read once.
+pub struct MarkerDetector<R: Read> {
+    /// The source of the datastream
+    bytes: Bytes<BufReader<R>>,
+
+    /// The last bytes read
+    window: MarkerWindow,
+
+    /// The number of bytes read so far
+    position: usize,
+}
+
+impl<R: Read> MarkerDetector<R> {
+    /// Create a detector for markers of `n` unique bytes in `reader`
+    pub fn new(reader: R, n: usize) -> Self {
+        Self {
+            bytes: BufReader::new(reader).bytes(),
+            window: MarkerWindow::new(n),
+            position: 0,
+        }
+    }
+}
+
+impl<R: Read> Iterator for MarkerDetector<R> {
+    /// The number of bytes read up to and including the end of each marker
+    type Item = std::io::Result<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let byte = match self.bytes.next()? {
+                Ok(byte) => byte,
+                Err(err) => return Some(Err(err)),
+            };
+
+            self.position += 1;
+
+            if self.window.push(byte) {
+                return Some(Ok(self.position));
+            }
+        }
+    }
+}
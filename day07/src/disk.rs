@@ -0,0 +1,133 @@
+//! Materialize an [`FsTree`] as a real directory and read a real directory back as
+//! a transcript
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use crate::fs::{FsTree, NodeId, NodeKind};
+
+/// Returns an error if `name` can't be used as a single path component
+fn check_name(name: &str) -> io::Result<()> {
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\0']) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{name:?} is not a valid file name"),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Write `tree` under the `target` directory, which must be empty or not exist yet.
+/// Files are created sparse with their recorded size, so they take no real space.
+pub fn export(tree: &FsTree, target: &Path) -> io::Result<()> {
+    std::fs::create_dir_all(target)?;
+
+    // Never mix the tree with existing files
+    if std::fs::read_dir(target)?.next().is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} is not empty", target.display()),
+        ));
+    }
+
+    export_dir(tree, FsTree::ROOT, target)
+}
+
+/// Write the children of `dir` into the existing directory `path`
+fn export_dir(tree: &FsTree, dir: NodeId, path: &Path) -> io::Result<()> {
+    for child in tree.children(dir) {
+        let node = tree.node(child);
+        check_name(&node.name)?;
+        let child_path = path.join(&node.name);
+
+        match node.kind {
+            NodeKind::Dir { .. } => {
+                std::fs::create_dir(&child_path)?;
+                export_dir(tree, child, &child_path)?;
+            }
+            NodeKind::File { size } => {
+                // Extending an empty file leaves a hole instead of writing zeros
+                File::create(&child_path)?.set_len(size)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Walk the directory `root` and emit the `$ cd`/`$ ls` transcript of exploring it
+///
+/// ```text
+/// $ cd /
+/// $ ls
+/// dir a
+/// 14848514 b.txt
+/// $ cd a
+/// $ ls
+/// ...
+/// $ cd ..
+/// ```
+pub fn import(root: &Path) -> io::Result<String> {
+    let mut transcript = String::from("$ cd /\n");
+    import_dir(root, &mut transcript)?;
+    Ok(transcript)
+}
+
+/// Append the listing of the directory `path` and then of each of its subdirectories
+fn import_dir(path: &Path, transcript: &mut String) -> io::Result<()> {
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+
+        let name = entry.file_name().into_string().map_err(|name| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{name:?} is not valid UTF-8"),
+            )
+        })?;
+
+        // A newline would end the line of the listing early
+        if name.contains('\n') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{name:?} contains a newline"),
+            ));
+        }
+
+        // Don't follow symlinks, which could loop back up the tree
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            entries.push((name, None));
+        } else if metadata.is_file() {
+            entries.push((name, Some(metadata.len())));
+        } else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is not a file or directory", entry.path().display()),
+            ));
+        }
+    }
+
+    // List in name order so the same directory always gives the same transcript
+    entries.sort();
+
+    transcript.push_str("$ ls\n");
+    for (name, size) in &entries {
+        match size {
+            Some(size) => transcript.push_str(&format!("{size} {name}\n")),
+            None => transcript.push_str(&format!("dir {name}\n")),
+        }
+    }
+
+    for (name, size) in &entries {
+        if size.is_none() {
+            transcript.push_str(&format!("$ cd {name}\n"));
+            import_dir(&path.join(name), transcript)?;
+            transcript.push_str("$ cd ..\n");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,312 @@
+//! In-memory filesystem tree rebuilt from a terminal transcript
+
+use std::collections::BTreeMap;
+
+/// The errors that can be triggered while replaying a transcript. Each holds the
+/// (1-indexed) line of the transcript where it was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FsError {
+    /// A line starting with `$` isn't a known command
+    UnknownCommand(usize),
+
+    /// A non-command line was found without a preceding `$ ls`
+    UnexpectedOutput(usize),
+
+    /// A line of `ls` output isn't `dir NAME` or `SIZE NAME`
+    InvalidListing(usize),
+
+    /// `cd` into a directory that wasn't listed by `ls` in the current directory
+    UnlistedDirectory(usize, String),
+
+    /// `cd ..` from the root directory
+    AboveRoot(usize),
+
+    /// The same name was listed twice by a single `ls`
+    DuplicateEntry(usize, String),
+
+    /// Listing an already listed directory again gave a different entry, or left out
+    /// an entry (reported at the line of the `ls`)
+    ListingMismatch(usize, String),
+}
+
+impl std::fmt::Display for FsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FsError::UnknownCommand(line) => write!(f, "line {line}: unknown command"),
+            FsError::UnexpectedOutput(line) => write!(f, "line {line}: output without `$ ls`"),
+            FsError::InvalidListing(line) => write!(f, "line {line}: invalid `ls` output"),
+            FsError::UnlistedDirectory(line, name) => {
+                write!(f, "line {line}: `cd` into unlisted directory {name:?}")
+            }
+            FsError::AboveRoot(line) => write!(f, "line {line}: `cd ..` from the root"),
+            FsError::DuplicateEntry(line, name) => {
+                write!(f, "line {line}: {name:?} listed twice")
+            }
+            FsError::ListingMismatch(line, name) => {
+                write!(f, "line {line}: {name:?} differs from the previous listing")
+            }
+        }
+    }
+}
+
+/// The index of a node in an [`FsTree`]
+pub type NodeId = usize;
+
+/// The contents of a node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeKind {
+    /// A directory with its children by name
+    Dir {
+        /// The children of the directory, sorted by name
+        children: BTreeMap<String, NodeId>,
+
+        /// Whether the directory was listed by `ls`
+        listed: bool,
+    },
+
+    /// A file with its size
+    File {
+        /// The size of the file in bytes
+        size: u64,
+    },
+}
+
+/// A single directory or file in the tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    /// The name of the node (`/` for the root)
+    pub name: String,
+
+    /// The directory containing this node, `None` for the root
+    pub parent: Option<NodeId>,
+
+    /// The contents of the node
+    pub kind: NodeKind,
+}
+
+/// A filesystem tree stored as an arena of nodes, with the root directory at index 0
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FsTree {
+    /// Every node in the tree
+    nodes: Vec<Node>,
+}
+
+impl FsTree {
+    /// The root directory of every tree
+    pub const ROOT: NodeId = 0;
+
+    /// Create a tree with only an empty root directory
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node {
+                name: "/".to_string(),
+                parent: None,
+                kind: NodeKind::Dir {
+                    children: BTreeMap::new(),
+                    listed: false,
+                },
+            }],
+        }
+    }
+
+    /// Get the node at `id`
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    /// Returns `true` if the node at `id` is a directory
+    pub fn is_dir(&self, id: NodeId) -> bool {
+        matches!(self.nodes[id].kind, NodeKind::Dir { .. })
+    }
+
+    /// Iterate over the children of `dir` sorted by name
+    pub fn children(&self, dir: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        let children = match &self.nodes[dir].kind {
+            NodeKind::Dir { children, .. } => Some(children.values().copied()),
+            NodeKind::File { .. } => None,
+        };
+
+        children.into_iter().flatten()
+    }
+
+    /// Get the child `name` of the directory `dir`
+    pub fn child(&self, dir: NodeId, name: &str) -> Option<NodeId> {
+        match &self.nodes[dir].kind {
+            NodeKind::Dir { children, .. } => children.get(name).copied(),
+            NodeKind::File { .. } => None,
+        }
+    }
+
+    /// Add a new node `name` with `kind` to the directory `parent`
+    fn add(&mut self, parent: NodeId, name: &str, kind: NodeKind) -> NodeId {
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            name: name.to_string(),
+            parent: Some(parent),
+            kind,
+        });
+
+        if let NodeKind::Dir { children, .. } = &mut self.nodes[parent].kind {
+            children.insert(name.to_string(), id);
+        }
+
+        id
+    }
+
+    /// Rebuild the tree by replaying the `cd` and `ls` commands of a transcript
+    pub fn from_transcript(transcript: &str) -> Result<Self, FsError> {
+        let mut tree = FsTree::new();
+        let mut cwd = FsTree::ROOT;
+
+        // The state of the `ls` being read: the line of the command, whether the
+        // directory was already listed before and the names seen in this listing
+        let mut listing: Option<(usize, bool, Vec<String>)> = None;
+
+        for (index, line) in transcript.lines().enumerate() {
+            let line_number = index + 1;
+
+            if let Some(command) = line.strip_prefix("$ ") {
+                // Any command ends the previous listing
+                if let Some(listing) = listing.take() {
+                    tree.finish_listing(cwd, listing)?;
+                }
+
+                match command.split_once(' ') {
+                    Some(("cd", "/")) => cwd = FsTree::ROOT,
+                    Some(("cd", "..")) => {
+                        cwd = tree.nodes[cwd]
+                            .parent
+                            .ok_or(FsError::AboveRoot(line_number))?;
+                    }
+                    Some(("cd", name)) => {
+                        cwd = tree
+                            .child(cwd, name)
+                            .filter(|id| tree.is_dir(*id))
+                            .ok_or_else(|| {
+                                FsError::UnlistedDirectory(line_number, name.to_string())
+                            })?;
+                    }
+                    None if command == "ls" => {
+                        let NodeKind::Dir { listed, .. } = tree.nodes[cwd].kind else {
+                            unreachable!("The working directory is always a directory");
+                        };
+
+                        listing = Some((line_number, listed, Vec::new()));
+                    }
+                    _ => return Err(FsError::UnknownCommand(line_number)),
+                }
+
+                continue;
+            }
+
+            // Expected line format: `dir bsnqsfm` or `221336 gdjfp.mfp`
+            let (_, already_listed, seen) = listing
+                .as_mut()
+                .ok_or(FsError::UnexpectedOutput(line_number))?;
+
+            let (kind, name) = match line.split_once(' ') {
+                Some(("dir", name)) => (
+                    NodeKind::Dir {
+                        children: BTreeMap::new(),
+                        listed: false,
+                    },
+                    name,
+                ),
+                Some((size, name)) => {
+                    let size = size
+                        .parse::<u64>()
+                        .map_err(|_| FsError::InvalidListing(line_number))?;
+                    (NodeKind::File { size }, name)
+                }
+                None => return Err(FsError::InvalidListing(line_number)),
+            };
+
+            if seen.iter().any(|seen_name| seen_name == name) {
+                return Err(FsError::DuplicateEntry(line_number, name.to_string()));
+            }
+            seen.push(name.to_string());
+
+            match (tree.child(cwd, name), &kind) {
+                // Listing a directory again must give the same entries
+                (Some(id), NodeKind::File { size }) => {
+                    if tree.nodes[id].kind != (NodeKind::File { size: *size }) {
+                        return Err(FsError::ListingMismatch(line_number, name.to_string()));
+                    }
+                }
+                (Some(id), NodeKind::Dir { .. }) => {
+                    if !tree.is_dir(id) {
+                        return Err(FsError::ListingMismatch(line_number, name.to_string()));
+                    }
+                }
+                (None, _) if *already_listed => {
+                    return Err(FsError::ListingMismatch(line_number, name.to_string()));
+                }
+                (None, _) => {
+                    tree.add(cwd, name, kind);
+                }
+            }
+        }
+
+        if let Some(listing) = listing {
+            tree.finish_listing(cwd, listing)?;
+        }
+
+        Ok(tree)
+    }
+
+    /// End the `ls` of the directory `dir` started at `line`, marking the directory
+    /// as listed. Listing a directory again must give every entry it already has.
+    fn finish_listing(
+        &mut self,
+        dir: NodeId,
+        (line, already_listed, seen): (usize, bool, Vec<String>),
+    ) -> Result<(), FsError> {
+        let NodeKind::Dir { children, listed } = &mut self.nodes[dir].kind else {
+            unreachable!("Only directories are listed");
+        };
+
+        if already_listed {
+            if let Some(missing) = children.keys().find(|name| !seen.contains(name)) {
+                return Err(FsError::ListingMismatch(line, missing.clone()));
+            }
+        }
+
+        *listed = true;
+        Ok(())
+    }
+
+    /// The total size of every directory in the tree, indexed by [`NodeId`]. Files have
+    /// no entry (`None`).
+    pub fn dir_sizes(&self) -> Vec<Option<u64>> {
+        let mut sizes = vec![None; self.nodes.len()];
+
+        // Children are always added after their parent, so walking the arena backwards
+        // sees every child before its parent
+        let mut totals = vec![0; self.nodes.len()];
+        for (id, node) in self.nodes.iter().enumerate().rev() {
+            match node.kind {
+                NodeKind::File { size } => totals[id] = size,
+                NodeKind::Dir { .. } => sizes[id] = Some(totals[id]),
+            }
+
+            if let Some(parent) = node.parent {
+                totals[parent] += totals[id];
+            }
+        }
+
+        sizes
+    }
+
+    /// The absolute path of the node `id`
+    pub fn path(&self, id: NodeId) -> String {
+        let mut names = Vec::new();
+        let mut curr = id;
+        while let Some(parent) = self.nodes[curr].parent {
+            names.push(self.nodes[curr].name.as_str());
+            curr = parent;
+        }
+
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+}
@@ -1,75 +1,208 @@
-use std::collections::HashMap;
+mod disk;
+mod fs;
+mod query;
+
 use std::path::PathBuf;
 
+use fs::{FsError, FsTree};
+use query::{FindFilter, Query};
+
 const INPUT: &str = include_str!("../input");
 
-fn main() {
-    let mut curr_dir = PathBuf::from("/");
-    let mut curr_sizes = HashMap::new();
-
-    // Parse the input lines
-    for line in INPUT.lines() {
-        if line.starts_with("$ ls") || line.starts_with("dir") {
-            // Ignore the `ls` command and `dir` commands
-            continue;
-        } else if line.starts_with("$ cd") {
-            // Expected line format: $ cd bsnqsfm
-
-            // Get the directory from the line
-            let directory = line.split(' ').nth(2).unwrap();
-            match &directory {
-                // Reset the path to root
-                &"/" => {
-                    curr_dir = PathBuf::from("/");
-                }
-                // Go up a directory to the parent
-                &".." => {
-                    curr_dir.pop();
-                }
-                // Add the given directory to the current path
-                dir => {
-                    curr_dir = curr_dir.join(dir);
-                }
-            }
-        } else {
-            // Expected line format: 221336 gdjfp.mfp
-            let size = line.split(' ').next().unwrap().parse::<u32>().unwrap();
-
-            // Clone the current working directory in preparation of adding the current
-            // file size to all parent directories
-            let mut tmp_dir = curr_dir.clone();
-            *curr_sizes.entry(tmp_dir.clone()).or_insert(0) += size;
-
-            // Add the current file size to each parent directory
-            while tmp_dir.pop() {
-                *curr_sizes.entry(tmp_dir.clone()).or_insert(0) += size;
+/// The largest directory counted by part 1
+const PART1_MAX_SIZE: u64 = 100_000;
+
+/// The total size of the device's disk
+const DISK_SIZE: u64 = 70_000_000;
+
+/// The free space needed to run the update
+const NEEDED_SPACE: u64 = 30_000_000;
+
+/// The errors that can be triggered during Day 07
+#[derive(Debug)]
+enum Day07Error {
+    /// Failed to read the given transcript
+    Io(std::io::Error),
+
+    /// The transcript is inconsistent
+    Fs(FsError),
+
+    /// Invalid command line argument
+    InvalidArgument(String),
+
+    /// The transcript read back from an exported tree gave a different tree
+    RoundTripMismatch,
+}
+
+impl std::fmt::Display for Day07Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Day07Error::Io(err) => write!(f, "{err}"),
+            Day07Error::Fs(err) => write!(f, "{err}"),
+            Day07Error::InvalidArgument(arg) => write!(f, "invalid argument: {arg:?}"),
+            Day07Error::RoundTripMismatch => {
+                write!(f, "the exported tree doesn't read back the same")
             }
         }
     }
+}
+
+/// Parse the value following the `flag` argument
+fn flag_value<T: std::str::FromStr>(
+    flag: &str,
+    args: &mut impl Iterator<Item = String>,
+) -> Result<T, Day07Error> {
+    args.next()
+        .and_then(|value| value.parse::<T>().ok())
+        .ok_or_else(|| Day07Error::InvalidArgument(flag.to_string()))
+}
 
+/// Solve both parts of the puzzle
+fn solve(query: &Query) {
     // Calculate the size of all directories under 100_000 bytes
-    let sum: u32 = curr_sizes
-        .iter()
-        .filter_map(|(_, size)| if *size <= 100000 { Some(size) } else { None })
-        .sum();
+    let small_dirs = query.find(&FindFilter {
+        dirs: Some(true),
+        max_size: Some(PART1_MAX_SIZE),
+        ..FindFilter::default()
+    });
+    let sum: u64 = small_dirs.iter().map(|id| query.size(*id)).sum();
 
     println!("Part 1: {sum}");
 
     // Get the total size of the entire filesystem from /
-    let total_size = curr_sizes
-        .get(&PathBuf::from("/"))
-        .expect("Root directory ('/') not found");
-
-    // Calculate the space needed at minimum to reach the 30_000_000 bytes needed
-    let size_needed = 30_000_000 - (70_000_000 - total_size);
-    let mut curr_best = u32::MAX;
+    let total_size = query.size(FsTree::ROOT);
 
     // Find the smallest directory that would free up enough space
-    for size in curr_sizes.values() {
-        if *size >= size_needed && *size < curr_best {
-            curr_best = *size;
+    let curr_best = query
+        .smallest_freeing(DISK_SIZE, NEEDED_SPACE)
+        .map_or(u64::MAX, |id| query.size(id));
+
+    println!("Part 2: total size: {total_size} Best: {curr_best}");
+}
+
+/// Usage: `day07 [--input FILE] [COMMAND]` where `COMMAND` is one of
+///
+/// - `du [--depth N]`: the size of every directory
+/// - `tree`: every node with its size
+/// - `find [--name PATTERN] [--type d|f] [--min-size N] [--max-size N]`
+/// - `top N`: the `N` largest directories
+/// - `free [--disk N] [--needed N]`: the smallest directory freeing enough space
+/// - `export DIR`: write the tree under `DIR` as sparse files
+/// - `import DIR`: print the transcript of exploring `DIR`
+/// - `roundtrip DIR`: export to `DIR` and check the imported transcript gives the same tree
+///
+/// Without a command, both parts of the puzzle are solved.
+fn main() -> Result<(), Day07Error> {
+    let mut args = std::env::args().skip(1).peekable();
+
+    let transcript = if args.next_if(|arg| arg == "--input").is_some() {
+        let path = args
+            .next()
+            .ok_or_else(|| Day07Error::InvalidArgument("--input".to_string()))?;
+        std::fs::read_to_string(path).map_err(Day07Error::Io)?
+    } else {
+        INPUT.to_string()
+    };
+
+    // Rebuild the filesystem from the `cd` and `ls` commands of the transcript
+    let tree = FsTree::from_transcript(&transcript).map_err(Day07Error::Fs)?;
+    let query = Query::new(&tree);
+
+    let Some(command) = args.next() else {
+        solve(&query);
+        return Ok(());
+    };
+
+    match command.as_str() {
+        "du" => {
+            let mut depth = None;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--depth" => depth = Some(flag_value(&arg, &mut args)?),
+                    _ => return Err(Day07Error::InvalidArgument(arg)),
+                }
+            }
+
+            for usage in query.du(depth) {
+                println!("{:<10} {}", usage.size, tree.path(usage.id));
+            }
+        }
+        "tree" => print!("{}", query.tree()),
+        "find" => {
+            let mut filter = FindFilter::default();
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--name" => filter.name = Some(flag_value(&arg, &mut args)?),
+                    "--type" => {
+                        filter.dirs = match args.next().as_deref() {
+                            Some("d") => Some(true),
+                            Some("f") => Some(false),
+                            _ => return Err(Day07Error::InvalidArgument(arg)),
+                        };
+                    }
+                    "--min-size" => filter.min_size = Some(flag_value(&arg, &mut args)?),
+                    "--max-size" => filter.max_size = Some(flag_value(&arg, &mut args)?),
+                    _ => return Err(Day07Error::InvalidArgument(arg)),
+                }
+            }
+
+            let found = query.find(&filter);
+            for id in &found {
+                println!("{:<10} {}", query.size(*id), tree.path(*id));
+            }
+
+            let total: u64 = found.iter().map(|id| query.size(*id)).sum();
+            println!("{} found, total size {total}", found.len());
+        }
+        "top" => {
+            let n = flag_value(&command, &mut args)?;
+            for id in query.largest_dirs(n) {
+                println!("{:<10} {}", query.size(id), tree.path(id));
+            }
+        }
+        "free" => {
+            let mut disk_size = DISK_SIZE;
+            let mut needed = NEEDED_SPACE;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--disk" => disk_size = flag_value(&arg, &mut args)?,
+                    "--needed" => needed = flag_value(&arg, &mut args)?,
+                    _ => return Err(Day07Error::InvalidArgument(arg)),
+                }
+            }
+
+            match query.smallest_freeing(disk_size, needed) {
+                Some(id) => println!("{:<10} {}", query.size(id), tree.path(id)),
+                None => println!("No directory frees {needed} bytes on a {disk_size} byte disk"),
+            }
         }
+        "export" | "import" | "roundtrip" => {
+            let dir = args
+                .next()
+                .map(PathBuf::from)
+                .ok_or_else(|| Day07Error::InvalidArgument(command.clone()))?;
+
+            if command != "import" {
+                disk::export(&tree, &dir).map_err(Day07Error::Io)?;
+            }
+
+            let imported = disk::import(&dir).map_err(Day07Error::Io)?;
+            if command == "import" {
+                print!("{imported}");
+            }
+
+            if command == "roundtrip" {
+                // Both trees are drawn in name order, so equal drawings are equal trees
+                let reimported = FsTree::from_transcript(&imported).map_err(Day07Error::Fs)?;
+                if Query::new(&reimported).tree() != query.tree() {
+                    return Err(Day07Error::RoundTripMismatch);
+                }
+
+                println!("Round trip through {} OK", dir.display());
+            }
+        }
+        _ => return Err(Day07Error::InvalidArgument(command)),
     }
 
-    println!("Part 2: total size: {total_size} Best: {curr_best}");
+    Ok(())
 }
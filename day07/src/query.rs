@@ -0,0 +1,198 @@
+//! Queries over a parsed [`FsTree`], modeled after `du`, `tree` and `find`
+
+use crate::fs::{FsTree, NodeId, NodeKind};
+
+/// A directory with its total size, as reported by [`Query::du`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DirUsage {
+    /// The directory
+    pub id: NodeId,
+
+    /// The depth of the directory below the root (the root is depth 0)
+    pub depth: usize,
+
+    /// The total size of every file under the directory
+    pub size: u64,
+}
+
+/// The criteria for [`Query::find`]. Every given criterion must match.
+#[derive(Debug, Default, Clone)]
+pub struct FindFilter {
+    /// A name pattern where `*` matches any run of characters and `?` any one
+    pub name: Option<String>,
+
+    /// Only match directories (`Some(true)`) or files (`Some(false)`)
+    pub dirs: Option<bool>,
+
+    /// The minimum size of a match
+    pub min_size: Option<u64>,
+
+    /// The maximum size of a match
+    pub max_size: Option<u64>,
+}
+
+/// Returns `true` if `name` matches the `pattern`, where `*` matches any run of
+/// characters and `?` matches any single character
+fn glob_match(pattern: &[char], name: &[char]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some('*'), _) => {
+            glob_match(&pattern[1..], name) || (!name.is_empty() && glob_match(pattern, &name[1..]))
+        }
+        (Some('?'), Some(_)) => glob_match(&pattern[1..], &name[1..]),
+        (Some(want), Some(have)) if want == have => glob_match(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
+
+/// The queries over a filesystem tree, using precomputed directory sizes
+pub struct Query<'a> {
+    /// The tree being queried
+    tree: &'a FsTree,
+
+    /// The total size of every node, indexed by [`NodeId`]
+    sizes: Vec<u64>,
+}
+
+impl<'a> Query<'a> {
+    /// Prepare the queries for `tree`
+    pub fn new(tree: &'a FsTree) -> Self {
+        let dir_sizes = tree.dir_sizes();
+        let sizes = dir_sizes
+            .iter()
+            .enumerate()
+            .map(|(id, dir_size)| match tree.node(id).kind {
+                NodeKind::File { size } => size,
+                NodeKind::Dir { .. } => dir_size.unwrap_or(0),
+            })
+            .collect();
+
+        Self { tree, sizes }
+    }
+
+    /// The total size of the node `id`
+    pub fn size(&self, id: NodeId) -> u64 {
+        self.sizes[id]
+    }
+
+    /// Every directory at most `max_depth` below the root (or all of them), listed
+    /// children first like `du`
+    pub fn du(&self, max_depth: Option<usize>) -> Vec<DirUsage> {
+        let mut result = Vec::new();
+        self.du_walk(FsTree::ROOT, 0, max_depth, &mut result);
+        result
+    }
+
+    /// Add the usage of `dir` and its subdirectories to `result`, children first
+    fn du_walk(
+        &self,
+        dir: NodeId,
+        depth: usize,
+        max_depth: Option<usize>,
+        result: &mut Vec<DirUsage>,
+    ) {
+        if max_depth.is_some_and(|max_depth| depth > max_depth) {
+            return;
+        }
+
+        for child in self.tree.children(dir) {
+            if self.tree.is_dir(child) {
+                self.du_walk(child, depth + 1, max_depth, result);
+            }
+        }
+
+        result.push(DirUsage {
+            id: dir,
+            depth,
+            size: self.sizes[dir],
+        });
+    }
+
+    /// Draw the tree with the size of every node, in the format of the puzzle example
+    ///
+    /// ```text
+    /// - / (dir, size=48381165)
+    ///   - a (dir, size=94853)
+    ///     - f (file, size=29116)
+    /// ```
+    pub fn tree(&self) -> String {
+        let mut result = String::new();
+        self.tree_walk(FsTree::ROOT, 0, &mut result);
+        result
+    }
+
+    /// Draw `id` and every node under it at `depth` into `result`
+    fn tree_walk(&self, id: NodeId, depth: usize, result: &mut String) {
+        let kind = if self.tree.is_dir(id) { "dir" } else { "file" };
+        result.push_str(&format!(
+            "{:indent$}- {} ({kind}, size={})\n",
+            "",
+            self.tree.node(id).name,
+            self.sizes[id],
+            indent = depth * 2
+        ));
+
+        for child in self.tree.children(id) {
+            self.tree_walk(child, depth + 1, result);
+        }
+    }
+
+    /// Every node matching `filter`, in depth first order
+    pub fn find(&self, filter: &FindFilter) -> Vec<NodeId> {
+        let pattern = filter
+            .name
+            .as_ref()
+            .map(|name| name.chars().collect::<Vec<_>>());
+
+        let mut result = Vec::new();
+        let mut stack = vec![FsTree::ROOT];
+        while let Some(id) = stack.pop() {
+            // Push the children in reverse so they are visited in name order
+            let mut children = self.tree.children(id).collect::<Vec<_>>();
+            children.reverse();
+            stack.extend(children);
+
+            let size = self.sizes[id];
+            let matches = filter.dirs.is_none_or(|dirs| dirs == self.tree.is_dir(id))
+                && filter.min_size.is_none_or(|min_size| size >= min_size)
+                && filter.max_size.is_none_or(|max_size| size <= max_size)
+                && pattern.as_ref().is_none_or(|pattern| {
+                    let name = self.tree.node(id).name.chars().collect::<Vec<_>>();
+                    glob_match(pattern, &name)
+                });
+
+            if matches {
+                result.push(id);
+            }
+        }
+
+        result
+    }
+
+    /// The `n` largest directories, largest first
+    pub fn largest_dirs(&self, n: usize) -> Vec<NodeId> {
+        let mut dirs = self.find(&FindFilter {
+            dirs: Some(true),
+            ..FindFilter::default()
+        });
+
+        dirs.sort_by_key(|id| std::cmp::Reverse(self.sizes[*id]));
+        dirs.truncate(n);
+        dirs
+    }
+
+    /// The smallest directory that, once deleted, leaves at least `needed` bytes free
+    /// on a disk of `disk_size` bytes. Returns `None` if no directory is large enough.
+    pub fn smallest_freeing(&self, disk_size: u64, needed: u64) -> Option<NodeId> {
+        let free = disk_size.saturating_sub(self.sizes[FsTree::ROOT]);
+        let to_free = needed.saturating_sub(free);
+
+        self.find(&FindFilter {
+            dirs: Some(true),
+            min_size: Some(to_free),
+            ..FindFilter::default()
+        })
+        .into_iter()
+        .min_by_key(|id| self.sizes[*id])
+    }
+}
@@ -0,0 +1,104 @@
+//! Export the per-tree results as PNG heatmaps and CSV
+
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use aoc2022::image::{write_rgb_png, Rgb};
+
+use crate::forest::{Forest, Grid};
+use crate::sweep::Distances;
+use crate::Day08Error;
+
+/// The color of trees visible from outside the forest
+const VISIBLE_COLOR: Rgb = [0x2e, 0xcc, 0x40];
+
+/// The color of hidden trees
+const HIDDEN_COLOR: Rgb = [0x11, 0x22, 0x11];
+
+/// Draw visible trees in green and hidden trees in dark green
+pub fn visible_png(path: &Path, visible: &Grid<bool>, scale: usize) -> Result<(), Day08Error> {
+    write_rgb_png(path, visible.width(), visible.height(), scale, |x, y| {
+        if visible[visible.index(y, x)] {
+            VISIBLE_COLOR
+        } else {
+            HIDDEN_COLOR
+        }
+    })
+    .map_err(Day08Error::Image)
+}
+
+/// The black to red to yellow to white heat color of `t` in `0.0..=1.0`
+fn heat(t: f64) -> Rgb {
+    // Each channel ramps up over its own third of the range
+    let channel = |start: f64| {
+        let value = ((t - start) * 3.0).clamp(0.0, 1.0) * 255.0;
+        value.round() as u8
+    };
+
+    [channel(0.0), channel(1.0 / 3.0), channel(2.0 / 3.0)]
+}
+
+/// Draw the scenic score of every tree on a log scale, so the few very high scores
+/// don't wash out the rest of the forest
+pub fn scenic_png(
+    path: &Path,
+    distances: &Grid<Distances>,
+    scale: usize,
+) -> Result<(), Day08Error> {
+    let log_score = |distances: &Distances| (distances.score() as f64).ln_1p();
+
+    let max = distances.cells().iter().map(log_score).fold(0.0, f64::max);
+
+    write_rgb_png(
+        path,
+        distances.width(),
+        distances.height(),
+        scale,
+        |x, y| {
+            if max == 0.0 {
+                heat(0.0)
+            } else {
+                heat(log_score(&distances[distances.index(y, x)]) / max)
+            }
+        },
+    )
+    .map_err(Day08Error::Image)
+}
+
+/// Write one CSV row per tree with its height, visibility and viewing distances
+///
+/// ```text
+/// row,column,height,visible,left,right,up,down,score
+/// 0,0,3,1,0,2,0,2,0
+/// ```
+pub fn csv(
+    out: impl Write,
+    forest: &Forest,
+    visible: &Grid<bool>,
+    distances: &Grid<Distances>,
+) -> std::io::Result<()> {
+    let mut out = BufWriter::new(out);
+    writeln!(out, "row,column,height,visible,left,right,up,down,score")?;
+
+    for row in 0..forest.height() {
+        for column in 0..forest.width() {
+            let index = forest.index(row, column);
+            let Distances {
+                left,
+                right,
+                up,
+                down,
+            } = distances[index];
+
+            writeln!(
+                out,
+                "{row},{column},{},{},{left},{right},{up},{down},{}",
+                forest[index],
+                u8::from(visible[index]),
+                distances[index].score()
+            )?;
+        }
+    }
+
+    out.flush()
+}
@@ -0,0 +1,151 @@
+//! The grid of tree heights
+
+use crate::Day08Error;
+
+/// A row-major grid of cells
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    /// The number of columns
+    width: usize,
+
+    /// The number of rows
+    height: usize,
+
+    /// The cells, row by row
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    /// Create a `width` by `height` grid with every cell set to `value`
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Create a `width` by `height` grid from its cells, row by row
+    pub fn from_cells(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(
+            cells.len(),
+            width * height,
+            "A grid has a cell per position"
+        );
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// The number of columns
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The number of rows
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The index into the cells of `(row, column)`
+    pub fn index(&self, row: usize, column: usize) -> usize {
+        debug_assert!(row < self.height && column < self.width);
+        row * self.width + column
+    }
+
+    /// The `(row, column)` of the cell at `index`
+    pub fn position(&self, index: usize) -> (usize, usize) {
+        (index / self.width, index % self.width)
+    }
+
+    /// The cells of `row`
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    /// Every cell, row by row
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+}
+
+impl<T> std::ops::Index<usize> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        &self.cells[index]
+    }
+}
+
+impl<T> std::ops::IndexMut<usize> for Grid<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut self.cells[index]
+    }
+}
+
+/// The height (0 to 9) of every tree in the forest
+pub type Forest = Grid<u8>;
+
+impl Forest {
+    /// Parse the puzzle input: one line of digits per row of trees
+    pub fn parse(input: &[u8]) -> Result<Self, Day08Error> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::with_capacity(input.len());
+
+        for (index, line) in input
+            .split(|x| *x == b'\n')
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty())
+        {
+            let line_number = index + 1;
+
+            // Every row must be as wide as the first one
+            if *width.get_or_insert(line.len()) != line.len() {
+                return Err(Day08Error::RaggedRow(line_number));
+            }
+
+            for (column, tree) in line.iter().enumerate() {
+                if !tree.is_ascii_digit() {
+                    return Err(Day08Error::InvalidHeight(line_number, column + 1));
+                }
+
+                cells.push(tree - b'0');
+            }
+
+            height += 1;
+        }
+
+        Ok(Self {
+            width: width.ok_or(Day08Error::EmptyForest)?,
+            height,
+            cells,
+        })
+    }
+
+    /// Generate a `size` by `size` forest of pseudo-random heights from `seed`
+    pub fn generate(size: usize, seed: u64) -> Self {
+        // xorshift64* never leaves the zero state, so avoid seeding it with zero
+        let mut state = seed | 1;
+
+        let cells = (0..size * size)
+            .map(|_| {
+                state ^= state >> 12;
+                state ^= state << 25;
+                state ^= state >> 27;
+                let value = state.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 32;
+                u8::try_from(value % 10).unwrap()
+            })
+            .collect();
+
+        Self {
+            width: size,
+            height: size,
+            cells,
+        }
+    }
+}
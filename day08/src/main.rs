@@ -1,172 +1,235 @@
+mod export;
+mod forest;
+mod naive;
+mod sweep;
+
+use std::path::PathBuf;
+use std::time::Instant;
+
+use forest::Forest;
+
 const INPUT: &[u8] = include_bytes!("../input");
 
-fn part1() {
-    let lines: Vec<_> = INPUT
-        .split(|x| *x == b'\n')
-        .filter(|line| !line.is_empty())
-        .collect();
+/// The errors that can be triggered during Day 08
+#[derive(Debug)]
+enum Day08Error {
+    /// A row isn't as wide as the first row. Holds the (1-indexed) line.
+    RaggedRow(usize),
 
-    let mut visible = Vec::new();
-    let height = lines.len();
+    /// A tree height isn't a digit. Holds the (1-indexed) line and column.
+    InvalidHeight(usize, usize),
 
-    'next_row: for (row, line) in lines.iter().enumerate() {
-        let width = line.len();
+    /// The input has no trees
+    EmptyForest,
 
-        if (row == 0) || (row == lines.len() - 1) {
-            // Every tree on the top and bottom rows are visible. Fast path here to
-            // allocate all trees being visible and continue to the next line
-            visible.push(vec![true; width]);
-            continue 'next_row;
-        } else {
-            // Allocate a visible state for each tree in this line
-            visible.push(vec![false; width]);
-        }
+    /// Invalid command line argument
+    InvalidArgument(String),
 
-        'next_col: for (column, curr_tree) in line.iter().enumerate() {
-            if column == 0 || column == width - 1 {
-                // Trees on the first and last column are always visible. Mark them as
-                // such
-                visible[row][column] = true;
-                continue 'next_col;
-            }
+    /// The sweeps disagree with the naive scans on the given result
+    Mismatch(&'static str),
 
-            // Naively search the four directions to see if this tree can be seen
+    /// Failed to write an export
+    Io(std::io::Error),
 
-            // Search down
-            if (row + 1..height).all(|curr_row| lines[curr_row][column] < *curr_tree) {
-                // Found a visible path down, mark as visible and move into the next tree
-                // since we only need one visible path
-                visible[row][column] = true;
-                continue 'next_col;
-            }
+    /// Failed to write a PNG
+    Image(aoc2022::image::ImageError),
+}
 
-            // Search left
-            if (0..column)
-                .rev()
-                .all(|curr_col| lines[row][curr_col] < *curr_tree)
-            {
-                // Found a visible path left, mark as visible and move into the next tree
-                // since we only need one visible path
-                visible[row][column] = true;
-                continue 'next_col;
+impl std::fmt::Display for Day08Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Day08Error::RaggedRow(line) => {
+                write!(f, "line {line}: row width differs from the first row")
             }
-
-            // Search right
-            if (column + 1..width).all(|curr_col| lines[row][curr_col] < *curr_tree) {
-                // Found a visible path right, mark as visible and move into the next tree
-                // since we only need one visible path
-                visible[row][column] = true;
-                continue 'next_col;
+            Day08Error::InvalidHeight(line, column) => {
+                write!(f, "line {line}: column {column} is not a digit")
             }
-
-            // Search up
-            if (0..row)
-                .rev()
-                .all(|curr_row| lines[curr_row][column] < *curr_tree)
-            {
-                // Found a visible path up, mark as visible and move into the next tree
-                // since we only need one visible path
-                visible[row][column] = true;
-                continue 'next_col;
+            Day08Error::EmptyForest => write!(f, "the input has no trees"),
+            Day08Error::InvalidArgument(arg) => write!(f, "invalid argument: {arg:?}"),
+            Day08Error::Mismatch(result) => {
+                write!(f, "{result} differs between the sweeps and the naive scans")
             }
+            Day08Error::Io(err) => write!(f, "{err}"),
+            Day08Error::Image(err) => write!(f, "{err}"),
         }
     }
-
-    println!(
-        "Visible: {}",
-        visible.iter().flatten().filter(|x| **x).count()
-    );
 }
 
-fn part2() {
-    let lines: Vec<_> = INPUT
-        .split(|x| *x == b'\n')
-        .filter(|line| !line.is_empty())
-        .collect();
+/// The command line options
+struct Options {
+    /// Generate a forest of this size instead of using the puzzle input
+    size: Option<usize>,
 
-    let mut scenic_scores = Vec::new();
-    let height = lines.len();
+    /// The seed of the generated forest
+    seed: u64,
 
-    for (row, line) in lines.iter().enumerate() {
-        let width = line.len();
+    /// Check the sweeps against the naive scans
+    verify: bool,
 
-        // Initialize the scenic scores
-        scenic_scores.push(vec![(0, 0, 0, 0); width]);
+    /// Print the position and viewing distances of the best tree
+    best: bool,
 
-        for (column, curr_tree) in line.iter().enumerate() {
-            // Search left
-            let mut score_left = 0;
-            for curr_col in (0..column).rev() {
-                // Every tree seen in the right direction counts
-                score_left += 1;
+    /// Write the per-tree results as CSV to this file, or stdout for `-`
+    csv: Option<PathBuf>,
 
-                // If the current tree is taller or equal to the current tree, this is
-                // the end of the eave
-                if lines[row][curr_col] >= *curr_tree {
-                    break;
-                }
-            }
+    /// Write the visible trees heatmap PNG to this file
+    visible_png: Option<PathBuf>,
 
-            // Search right
-            let mut score_right = 0;
-            for curr_col in column + 1..width {
-                // Every tree seen in the right direction counts
-                score_right += 1;
+    /// Write the scenic score heatmap PNG to this file
+    scenic_png: Option<PathBuf>,
 
-                // If the current tree is taller or equal to the current tree, this is
-                // the end of the eave
-                if lines[row][curr_col] >= *curr_tree {
-                    break;
+    /// The width and height in pixels of each tree in the PNGs
+    scale: usize,
+}
+
+impl Options {
+    /// Parse the command line:
+    ///
+    /// `day08 [--size N] [--seed N] [--verify] [--best] [--csv FILE|-]
+    ///        [--visible-png FILE] [--scenic-png FILE] [--scale N]`
+    fn parse() -> Result<Self, Day08Error> {
+        let mut options = Options {
+            size: None,
+            seed: 0x2022_1208,
+            verify: false,
+            best: false,
+            csv: None,
+            visible_png: None,
+            scenic_png: None,
+            scale: 4,
+        };
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--size" | "--scale" => {
+                    let value = args
+                        .next()
+                        .and_then(|value| value.parse::<usize>().ok())
+                        .filter(|value| *value > 0)
+                        .ok_or_else(|| Day08Error::InvalidArgument(arg.clone()))?;
+
+                    if arg == "--size" {
+                        options.size = Some(value);
+                    } else {
+                        options.scale = value;
+                    }
+                }
+                "--seed" => {
+                    options.seed = args
+                        .next()
+                        .and_then(|seed| seed.parse::<u64>().ok())
+                        .ok_or_else(|| Day08Error::InvalidArgument(arg.clone()))?;
+                }
+                "--verify" => options.verify = true,
+                "--best" => options.best = true,
+                "--csv" | "--visible-png" | "--scenic-png" => {
+                    let path = args
+                        .next()
+                        .map(PathBuf::from)
+                        .ok_or_else(|| Day08Error::InvalidArgument(arg.clone()))?;
+
+                    match arg.as_str() {
+                        "--csv" => options.csv = Some(path),
+                        "--visible-png" => options.visible_png = Some(path),
+                        _ => options.scenic_png = Some(path),
+                    }
                 }
+                _ => return Err(Day08Error::InvalidArgument(arg)),
             }
+        }
 
-            // Search up
-            let mut score_up = 0;
-            for curr_row in (0..row).rev() {
-                // Every tree seen in the up direction counts
-                score_up += 1;
+        Ok(options)
+    }
+}
 
-                // If the current tree is taller or equal to the current tree, this is
-                // the end of the eave
-                if lines[curr_row][column] >= *curr_tree {
-                    break;
-                }
-            }
+/// Run `f`, printing how long it took to stderr when `timed`
+fn timed<T>(timed: bool, name: &str, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
 
-            // Search down
-            let mut score_down = 0;
+    if timed {
+        eprintln!("{name}: {:.2?}", start.elapsed());
+    }
 
-            // Keep the same index form for all directions even if this loop could be
-            // written slightly differently according to clippy
-            #[allow(clippy::needless_range_loop)]
-            for curr_row in row + 1..height {
-                // Every tree seen in the down direction counts
-                score_down += 1;
+    result
+}
 
-                // If the current tree is taller or equal to the current tree, this is
-                // the end of the eave
-                if lines[curr_row][column] >= *curr_tree {
-                    break;
-                }
-            }
+fn main() -> Result<(), Day08Error> {
+    let options = Options::parse()?;
+
+    // Time the algorithms on generated forests, where it matters
+    let timing = options.size.is_some();
 
-            // Save the individual scores of each direction for debugging
-            scenic_scores[row][column] = (score_left, score_right, score_up, score_down);
+    let forest = match options.size {
+        Some(size) => timed(timing, "generate", || Forest::generate(size, options.seed)),
+        None => Forest::parse(INPUT)?,
+    };
+
+    let visible = timed(timing, "visible", || sweep::visible(&forest));
+    let distances = timed(timing, "viewing distances", || {
+        sweep::viewing_distances(&forest)
+    });
+
+    if options.verify {
+        if timed(timing, "naive visible", || naive::visible(&forest)) != visible {
+            return Err(Day08Error::Mismatch("visibility"));
+        }
+
+        let naive_distances = timed(timing, "naive viewing distances", || {
+            naive::viewing_distances(&forest)
+        });
+        if naive_distances != distances {
+            return Err(Day08Error::Mismatch("viewing distances"));
+        }
+
+        eprintln!(
+            "Verified {}x{} forest against the naive scans",
+            forest.width(),
+            forest.height()
+        );
+    }
+
+    if let Some(path) = &options.csv {
+        if path.as_os_str() == "-" {
+            export::csv(std::io::stdout().lock(), &forest, &visible, &distances)
+        } else {
+            let file = std::fs::File::create(path).map_err(Day08Error::Io)?;
+            export::csv(file, &forest, &visible, &distances)
         }
+        .map_err(Day08Error::Io)?;
     }
 
+    if let Some(path) = &options.visible_png {
+        export::visible_png(path, &visible, options.scale)?;
+    }
+
+    if let Some(path) = &options.scenic_png {
+        export::scenic_png(path, &distances, options.scale)?;
+    }
+
+    println!(
+        "Visible: {}",
+        visible.cells().iter().filter(|x| **x).count()
+    );
+
     // Calculate each of the scenic scores for all trees and get the best one
     println!(
         "Best scenic score: {:?}",
-        scenic_scores
-            .iter()
-            .flatten()
-            .map(|(a, b, c, d)| a * b * c * d)
-            .max()
+        distances.cells().iter().map(sweep::Distances::score).max()
     );
-}
 
-fn main() {
-    part1();
-    part2();
+    if options.best {
+        if let Some(index) = sweep::best_tree(&distances) {
+            let (row, column) = distances.position(index);
+            let best = distances[index];
+            println!(
+                "Best tree: row {row} column {column} height {} \
+                 (left {}, right {}, up {}, down {})",
+                forest[index], best.left, best.right, best.up, best.down
+            );
+        }
+    }
+
+    Ok(())
 }
@@ -0,0 +1,156 @@
+//! The original ray scans from every tree, O(n³) on an n×n forest
+//!
+//! These are kept as the oracle the sweeps are verified against.
+
+use crate::forest::{Forest, Grid};
+use crate::sweep::Distances;
+
+/// Scan the four directions from every tree to check if it can be seen from outside
+/// the forest
+pub fn visible(forest: &Forest) -> Grid<bool> {
+    let lines: Vec<_> = (0..forest.height()).map(|row| forest.row(row)).collect();
+
+    let mut visible = Grid::new(forest.width(), forest.height(), false);
+    let height = lines.len();
+
+    for (row, line) in lines.iter().enumerate() {
+        let width = line.len();
+
+        'next_col: for (column, curr_tree) in line.iter().enumerate() {
+            let index = visible.index(row, column);
+
+            if row == 0 || row == height - 1 || column == 0 || column == width - 1 {
+                // Trees on the edges are always visible. Mark them as such
+                visible[index] = true;
+                continue 'next_col;
+            }
+
+            // Naively search the four directions to see if this tree can be seen
+
+            // Search down
+            if (row + 1..height).all(|curr_row| lines[curr_row][column] < *curr_tree) {
+                // Found a visible path down, mark as visible and move into the next tree
+                // since we only need one visible path
+                visible[index] = true;
+                continue 'next_col;
+            }
+
+            // Search left
+            if (0..column)
+                .rev()
+                .all(|curr_col| lines[row][curr_col] < *curr_tree)
+            {
+                // Found a visible path left, mark as visible and move into the next tree
+                // since we only need one visible path
+                visible[index] = true;
+                continue 'next_col;
+            }
+
+            // Search right
+            if (column + 1..width).all(|curr_col| lines[row][curr_col] < *curr_tree) {
+                // Found a visible path right, mark as visible and move into the next tree
+                // since we only need one visible path
+                visible[index] = true;
+                continue 'next_col;
+            }
+
+            // Search up
+            if (0..row)
+                .rev()
+                .all(|curr_row| lines[curr_row][column] < *curr_tree)
+            {
+                // Found a visible path up, mark as visible and move into the next tree
+                // since we only need one visible path
+                visible[index] = true;
+                continue 'next_col;
+            }
+        }
+    }
+
+    visible
+}
+
+/// Walk the four directions from every tree until the view is blocked
+pub fn viewing_distances(forest: &Forest) -> Grid<Distances> {
+    let lines: Vec<_> = (0..forest.height()).map(|row| forest.row(row)).collect();
+
+    let mut scenic_scores = Grid::new(forest.width(), forest.height(), Distances::default());
+    let height = lines.len();
+
+    for (row, line) in lines.iter().enumerate() {
+        let width = line.len();
+
+        for (column, curr_tree) in line.iter().enumerate() {
+            // Search left
+            let mut score_left = 0;
+            for curr_col in (0..column).rev() {
+                // Every tree seen in the right direction counts
+                score_left += 1;
+
+                // If the current tree is taller or equal to the current tree, this is
+                // the end of the eave
+                if lines[row][curr_col] >= *curr_tree {
+                    break;
+                }
+            }
+
+            // Search right
+            let mut score_right = 0;
+
+            // Keep the same index form for all directions even if this loop could be
+            // written slightly differently according to clippy
+            #[allow(clippy::needless_range_loop)]
+            for curr_col in column + 1..width {
+                // Every tree seen in the right direction counts
+                score_right += 1;
+
+                // If the current tree is taller or equal to the current tree, this is
+                // the end of the eave
+                if lines[row][curr_col] >= *curr_tree {
+                    break;
+                }
+            }
+
+            // Search up
+            let mut score_up = 0;
+            for curr_row in (0..row).rev() {
+                // Every tree seen in the up direction counts
+                score_up += 1;
+
+                // If the current tree is taller or equal to the current tree, this is
+                // the end of the eave
+                if lines[curr_row][column] >= *curr_tree {
+                    break;
+                }
+            }
+
+            // Search down
+            let mut score_down = 0;
+
+            // Keep the same index form for all directions even if this loop could be
+            // written slightly differently according to clippy
+            #[allow(clippy::needless_range_loop)]
+            for curr_row in row + 1..height {
+                // Every tree seen in the down direction counts
+                score_down += 1;
+
+                // If the current tree is taller or equal to the current tree, this is
+                // the end of the eave
+                if lines[curr_row][column] >= *curr_tree {
+                    break;
+                }
+            }
+
+            // Save the individual scores of each direction for debugging
+            let index = scenic_scores.index(row, column);
+            scenic_scores[index] = Distances {
+                left: score_left,
+                right: score_right,
+                up: score_up,
+                down: score_down,
+            };
+        }
+    }
+
+    scenic_scores
+}
@@ -0,0 +1,216 @@
+//! Visibility and viewing distances in O(n²) on an n×n forest
+//!
+//! Each row and column is swept once from each end: visibility only needs the tallest
+//! tree seen so far, and the viewing distance of a tree is the distance to the closest
+//! earlier tree at least as tall, which a monotonic stack of the trees that can still
+//! block the view gives directly. Columns are swept a whole row at a time, with one
+//! stack per column, to walk the grid in memory order.
+
+use crate::forest::{Forest, Grid};
+
+/// The number of trees seen from a tree in each direction before the view is blocked.
+/// Stored as `u32` to keep a grid of them small: a forest is never 2³² trees wide.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Distances {
+    /// Trees seen looking left
+    pub left: u32,
+
+    /// Trees seen looking right
+    pub right: u32,
+
+    /// Trees seen looking up
+    pub up: u32,
+
+    /// Trees seen looking down
+    pub down: u32,
+}
+
+impl Distances {
+    /// The scenic score: the product of the four viewing distances
+    pub fn score(&self) -> u64 {
+        [self.left, self.right, self.up, self.down]
+            .into_iter()
+            .map(u64::from)
+            .product()
+    }
+}
+
+/// The index of the tree with the best scenic score, the first one in reading order
+/// if several trees share it
+pub fn best_tree(distances: &Grid<Distances>) -> Option<usize> {
+    distances
+        .cells()
+        .iter()
+        .enumerate()
+        .max_by_key(|(index, distances)| (distances.score(), std::cmp::Reverse(*index)))
+        .map(|(index, _)| index)
+}
+
+/// Returns `true` if `tree` is taller than the `tallest` tree seen so far on its line,
+/// updating `tallest`
+fn taller(tallest: &mut Option<u8>, tree: u8) -> bool {
+    if tallest.is_some_and(|tallest| tree <= tallest) {
+        return false;
+    }
+
+    *tallest = Some(tree);
+    true
+}
+
+/// Mark every tree that can be seen from outside the forest
+pub fn visible(forest: &Forest) -> Grid<bool> {
+    let (width, height) = (forest.width(), forest.height());
+    let mut visible = Grid::new(width, height, false);
+
+    // Sweep each row from the left and from the right
+    for row in 0..height {
+        let (mut from_left, mut from_right) = (None, None);
+        for column in 0..width {
+            let index = forest.index(row, column);
+            if taller(&mut from_left, forest[index]) {
+                visible[index] = true;
+            }
+
+            let index = forest.index(row, width - 1 - column);
+            if taller(&mut from_right, forest[index]) {
+                visible[index] = true;
+            }
+        }
+    }
+
+    // Sweep every column at once from the top and from the bottom
+    let mut from_top = vec![None; width];
+    let mut from_bottom = vec![None; width];
+    for row in 0..height {
+        for column in 0..width {
+            let index = forest.index(row, column);
+            if taller(&mut from_top[column], forest[index]) {
+                visible[index] = true;
+            }
+
+            let index = forest.index(height - 1 - row, column);
+            if taller(&mut from_bottom[column], forest[index]) {
+                visible[index] = true;
+            }
+        }
+    }
+
+    visible
+}
+
+/// The trees that can still block the view of later trees on a line, as a monotonic
+/// stack of their positions on the line. Heights are strictly decreasing from the
+/// bottom of the stack, so it never holds more than one tree of each height.
+#[derive(Debug, Default, Copy, Clone)]
+struct ViewStack {
+    /// The position of every tree on the stack, bottom first
+    positions: [u32; 10],
+
+    /// The number of trees on the stack
+    len: usize,
+
+    /// Bit `h` is set if a tree of height `h` is on the stack
+    heights: u16,
+}
+
+impl ViewStack {
+    /// The viewing distance back along the line of the `tree` at `position`, pushing
+    /// it onto the stack of the trees before it
+    fn view(&mut self, position: u32, tree: u8) -> u32 {
+        // Shorter trees are hidden behind this one for every later tree. They are all
+        // at the top of the stack, so pop them at once by counting them rather than
+        // one at a time, which random heights mispredict constantly.
+        let shorter = (1 << tree) - 1;
+        self.len -= (self.heights & shorter).count_ones() as usize;
+        self.heights &= !shorter;
+
+        // The view stops at the closest tree at least as tall, or at the edge
+        let top = self.positions[self.len.saturating_sub(1)];
+        let blocker = if self.len == 0 { 0 } else { top };
+
+        // An earlier tree of the same height is now always behind this one
+        self.len -= usize::from(self.heights >> tree & 1 == 1);
+
+        self.positions[self.len] = position;
+        self.len += 1;
+        self.heights |= 1 << tree;
+
+        position - blocker
+    }
+}
+
+/// The viewing distances in all four directions of every tree
+pub fn viewing_distances(forest: &Forest) -> Grid<Distances> {
+    let (width, height) = (forest.width(), forest.height());
+    let mut cells = Vec::with_capacity(width * height);
+
+    // Sweeping from an edge gives the distance looking back towards it. Large grids
+    // don't fit in the cache, so the left, right and up sweeps share one pass down the
+    // rows and only the down sweep needs a second pass back up.
+    let mut from_top = vec![ViewStack::default(); width];
+    for (row, trees) in (0..height).map(|row| (row, forest.row(row))) {
+        let mut from_left = ViewStack::default();
+        let start = cells.len();
+        cells.extend(trees.iter().zip(&mut from_top).zip(0..).map(
+            |((&tree, from_top), position)| Distances {
+                left: from_left.view(position, tree),
+                up: from_top.view(row as u32, tree),
+                ..Distances::default()
+            },
+        ));
+
+        // The row was just written, so it is still in the cache
+        let mut from_right = ViewStack::default();
+        for ((distances, &tree), position) in cells[start..].iter_mut().zip(trees).rev().zip(0..) {
+            distances.right = from_right.view(position, tree);
+        }
+    }
+
+    let mut from_bottom = vec![ViewStack::default(); width];
+    for (row, position) in cells.chunks_mut(width.max(1)).rev().zip(0..) {
+        let trees = forest.row(height - 1 - position as usize);
+        for ((distances, &tree), from_bottom) in row.iter_mut().zip(trees).zip(&mut from_bottom) {
+            distances.down = from_bottom.view(position, tree);
+        }
+    }
+
+    Grid::from_cells(width, height, cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::naive;
+
+    /// The sweeps agree with the naive scans, down to the smallest forests where every
+    /// tree is on an edge
+    #[test]
+    fn matches_naive() {
+        for size in [1, 2, 3, 5, 17, 64] {
+            for seed in [0, 1, 2, 42, 0xdead_beef] {
+                let forest = Forest::generate(size, seed);
+                assert_eq!(
+                    visible(&forest),
+                    naive::visible(&forest),
+                    "{size}x{size} seed {seed}"
+                );
+                assert_eq!(
+                    viewing_distances(&forest),
+                    naive::viewing_distances(&forest),
+                    "{size}x{size} seed {seed}"
+                );
+            }
+        }
+    }
+
+    /// The sweeps agree with the naive scans on the puzzle input
+    #[test]
+    fn matches_naive_on_input() {
+        let forest = Forest::parse(crate::INPUT).unwrap();
+        assert_eq!(visible(&forest), naive::visible(&forest));
+        assert_eq!(
+            viewing_distances(&forest),
+            naive::viewing_distances(&forest)
+        );
+    }
+}
@@ -1,273 +1,333 @@
 #![feature(iter_next_chunk)]
-#![feature(result_option_inspect)]
 
-use std::collections::BTreeSet;
+mod rope;
+mod space;
+mod trace;
+
+use std::path::PathBuf;
+
+use rope::{Direction, Location, Point, Rope};
+use space::{Direction3, Location3};
+use trace::Trace;
 
 const INPUT: &str = include_str!("../input");
 
-#[derive(Debug, Copy, Clone)]
-enum Day09Error {
+#[derive(Debug)]
+pub enum Day09Error {
     /// Invalid direction character
-    InvalidDirection(&'static str),
+    InvalidDirection(String),
 
     /// Line failed to parse
-    ParseLineFail(&'static str),
+    ParseLineFail(String),
+
+    /// A rope needs at least the head knot
+    EmptyRope,
+
+    /// Invalid command line argument
+    InvalidArgument(String),
+
+    /// The 3-dimensional rope of this many knots didn't visit the same locations as
+    /// the 2-dimensional one
+    Mismatch3d(usize),
+
+    /// Failed to read the motions or write a trace
+    Io(std::io::Error),
+
+    /// Failed to write a PNG
+    Image(aoc2022::image::ImageError),
 }
 
-/// A direction that the rope can move in a 2-dimensional plane
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+impl std::fmt::Display for Day09Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Day09Error::InvalidDirection(direction) => {
+                write!(f, "invalid direction: {direction:?}")
+            }
+            Day09Error::ParseLineFail(line) => write!(f, "invalid motion: {line:?}"),
+            Day09Error::EmptyRope => write!(f, "a rope needs at least one knot"),
+            Day09Error::InvalidArgument(arg) => write!(f, "invalid argument: {arg:?}"),
+            Day09Error::Mismatch3d(knots) => {
+                write!(f, "the 2D and 3D ropes of {knots} knots differ")
+            }
+            Day09Error::Io(err) => write!(f, "{err}"),
+            Day09Error::Image(err) => write!(f, "{err}"),
+        }
+    }
 }
 
-impl TryFrom<&'static str> for Direction {
-    type Error = Day09Error;
+/// Parse each line `U 1` of the input into `(Direction::Up, 1)`
+fn parse_motions<'a, D>(input: &'a str) -> Result<Vec<(D, u32)>, Day09Error>
+where
+    D: TryFrom<&'a str, Error = Day09Error>,
+{
+    let mut motions = Vec::new();
 
-    fn try_from(value: &'static str) -> Result<Self, Self::Error> {
-        match value {
-            "U" => Ok(Direction::Up),
-            "D" => Ok(Direction::Down),
-            "L" => Ok(Direction::Left),
-            "R" => Ok(Direction::Right),
-            _ => Err(Day09Error::InvalidDirection(value)),
+    for line in input.split('\n') {
+        // Ignore empty lines
+        if line.is_empty() {
+            continue;
         }
+
+        let Ok([direction, number_of_steps]) = line.split(' ').next_chunk() else {
+            return Err(Day09Error::ParseLineFail(line.to_string()));
+        };
+
+        let direction = D::try_from(direction)?;
+        let Ok(number_of_steps) = number_of_steps.parse::<u32>() else {
+            return Err(Day09Error::ParseLineFail(line.to_string()));
+        };
+
+        motions.push((direction, number_of_steps));
     }
-}
 
-/// A location on a 2-dimensional grid
-#[derive(Default, Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
-struct Location {
-    x: i32,
-    y: i32,
+    Ok(motions)
 }
 
-impl Location {
-    /// Step the location by the given [`Direction`]
-    pub fn step(&mut self, direction: Direction) {
-        match direction {
-            Direction::Up => self.y -= 1,
-            Direction::Down => self.y += 1,
-            Direction::Left => self.x -= 1,
-            Direction::Right => self.x += 1,
+/// Execute the rope simulation from Day 09 with a rope of `rope_length` knots
+fn simulation<P: Point>(
+    motions: &[(P::Direction, u32)],
+    rope_length: usize,
+) -> Result<Rope<P>, Day09Error> {
+    let mut rope = Rope::new(rope_length)?;
+
+    for &(direction, number_of_steps) in motions {
+        for _ in 0..number_of_steps {
+            rope.step(direction);
         }
     }
+
+    Ok(rope)
 }
 
-/// Execute the rope simulation from Day 09
-fn simulation(rope_length: usize) -> Result<usize, Day09Error> {
-    // n entries - 1 head and (n-1) tails
-    let mut rope = vec![Location::default(); rope_length];
+/// Print the result of both parts, or with `knots` the final location and number of
+/// visited locations of every knot of a single rope
+fn report<P: Point>(
+    motions: &[(P::Direction, u32)],
+    knots: Option<usize>,
+) -> Result<(), Day09Error> {
+    if let Some(knots) = knots {
+        let rope = simulation::<P>(motions, knots)?;
+        for (index, location) in rope.knots().iter().enumerate() {
+            let visited = rope.visited(index).map_or(0, |visited| visited.len());
+            println!("Knot {index} at {location} visited: {visited}");
+        }
+
+        return Ok(());
+    }
+
+    let part1 = simulation::<P>(motions, 2)?.tail_visited().len();
+    println!("Part 1 tailed moved: {part1}");
+    let part2 = simulation::<P>(motions, 10)?.tail_visited().len();
+    println!("Part 2 tailed moved: {part2}");
+
+    Ok(())
+}
 
-    // Allocate another rope to cache the previous location of each segment
-    let mut prev_rope = rope.clone();
+/// Check that ropes of 1 to `max_knots` knots visit the same locations in 3D as in
+/// 2D for motions that stay in the plane
+fn check_3d(
+    motions: &[(Direction, u32)],
+    motions_3d: &[(Direction3, u32)],
+    max_knots: usize,
+) -> Result<(), Day09Error> {
+    for knots in 1..=max_knots {
+        let rope = simulation::<Location>(motions, knots)?;
+        let rope_3d = simulation::<Location3>(motions_3d, knots)?;
 
-    // Initialize the set used to accumulate the locations the tail travels
-    let mut tail_steps = BTreeSet::new();
-    tail_steps.insert(rope[rope_length - 1]);
+        for index in 0..knots {
+            let visited = rope
+                .visited(index)
+                .into_iter()
+                .flatten()
+                .map(|location| Location3::from(*location));
 
-    for line in INPUT.split('\n') {
-        // Ignore empty lines
-        if line.is_empty() {
-            continue;
+            if !visited.eq(rope_3d.visited(index).into_iter().flatten().copied()) {
+                return Err(Day09Error::Mismatch3d(knots));
+            }
         }
+    }
+
+    println!("2D and 3D ropes of 1 to {max_knots} knots visit the same locations");
+    Ok(())
+}
+
+/// Open `path` for writing, or stdout for `-`
+fn create(path: &PathBuf) -> Result<Box<dyn std::io::Write>, Day09Error> {
+    if path.as_os_str() == "-" {
+        return Ok(Box::new(std::io::stdout().lock()));
+    }
+
+    let file = std::fs::File::create(path).map_err(Day09Error::Io)?;
+    Ok(Box::new(file))
+}
+
+/// The command line options
+#[derive(Default)]
+struct Options {
+    /// The motions file to read instead of the puzzle input
+    input: Option<PathBuf>,
+
+    /// The number of knots of a single rope to simulate
+    knots: Option<usize>,
+
+    /// Simulate the ropes in 3D space
+    three_d: bool,
+
+    /// Check the 3D ropes against the 2D ropes
+    check: bool,
 
-        // Parse each line `U 1` into `Direction::Up 1`
-        let Ok((Ok(direction), Ok(number_of_steps))) = line
-                .split(' ')
-                .next_chunk()
-                .map(|[left, right]| (Direction::try_from(left), right.parse::<u32>())) else {
-            return Err(Day09Error::ParseLineFail(line));
+    /// Write the trace of the rope as CSV to this file, or stdout for `-`
+    trace_csv: Option<PathBuf>,
+
+    /// Write the trace of the rope as JSON lines to this file, or stdout for `-`
+    trace_jsonl: Option<PathBuf>,
+
+    /// Draw the visited cells of the rope to this PNG file
+    png: Option<PathBuf>,
+
+    /// The width and height in pixels of each cell in the PNG
+    scale: usize,
+}
+
+impl Options {
+    /// Parse the command line:
+    ///
+    /// `day09 [--input FILE] [--knots N] [--3d] [--check-3d] [--trace-csv FILE|-]
+    ///        [--trace-jsonl FILE|-] [--png FILE] [--scale N]`
+    fn parse() -> Result<Self, Day09Error> {
+        let mut options = Options {
+            scale: 2,
+            ..Options::default()
         };
 
-        for _ in 0..number_of_steps {
-            // Simulate the movement of the head
-            rope[0].step(direction);
-
-            // Treat each 2 chunk window of the rope as a (head, tail) pair. The tail
-            // segment will only move when the head segment is a certain configuration
-            // away
-            for index in 1..rope.len() {
-                let curr_head = rope[index - 1];
-                let curr_tail = rope[index];
-
-                // Initialize the new position of the current segment
-                let mut new_position: Option<Location> = None;
-
-                // Rope is moving horizontally
-                // .....    .....    .....
-                // .TH.. -> .T.H. -> ..TH.
-                // .....    .....    .....
-                if (curr_head.x - curr_tail.x).abs() == 2 && (curr_head.y - curr_tail.y).abs() == 0
-                {
-                    if curr_head.x > curr_tail.x {
-                        new_position = Some(Location {
-                            x: curr_head.x - 1,
-                            y: curr_head.y,
-                        });
-                    } else {
-                        new_position = Some(Location {
-                            x: curr_head.x + 1,
-                            y: curr_head.y,
-                        });
-                    }
-                }
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--knots" | "--scale" => {
+                    let value = args
+                        .next()
+                        .and_then(|value| value.parse::<usize>().ok())
+                        .ok_or_else(|| Day09Error::InvalidArgument(arg.clone()))?;
 
-                // Rope is moving vertically
-                // ...    ...    ...
-                // .T.    .T.    ...
-                // .H. -> ... -> .T.
-                // ...    .H.    .H.
-                // ...    ...    ...
-                if (curr_head.y - curr_tail.y).abs() == 2 && (curr_head.x - curr_tail.x).abs() == 0
-                {
-                    if curr_head.y > curr_tail.y {
-                        new_position = Some(Location {
-                            x: curr_head.x,
-                            y: curr_head.y - 1,
-                        });
+                    if arg == "--knots" {
+                        options.knots = Some(value);
                     } else {
-                        new_position = Some(Location {
-                            x: curr_head.x,
-                            y: curr_head.y + 1,
-                        });
+                        options.scale = value;
                     }
                 }
+                "--3d" => options.three_d = true,
+                "--check-3d" => options.check = true,
+                "--input" | "--trace-csv" | "--trace-jsonl" | "--png" => {
+                    let path = args
+                        .next()
+                        .map(PathBuf::from)
+                        .ok_or_else(|| Day09Error::InvalidArgument(arg.clone()))?;
 
-                // Rope has moved into an L shaped pattern vertically
-                //  .....    .....    .....
-                // .....    ..H..    ..H..
-                // ..H.. -> ..... -> ..T..
-                // .T...    .T...    .....
-                // .....    .....    .....
-                if (curr_head.x - curr_tail.x).abs() == 1 && (curr_head.y - curr_tail.y).abs() == 2
-                {
-                    if curr_head.y > curr_tail.y {
-                        new_position = Some(Location {
-                            x: curr_head.x,
-                            y: curr_head.y - 1,
-                        });
-                    } else {
-                        new_position = Some(Location {
-                            x: curr_head.x,
-                            y: curr_head.y + 1,
-                        });
+                    match arg.as_str() {
+                        "--input" => options.input = Some(path),
+                        "--trace-csv" => options.trace_csv = Some(path),
+                        "--trace-jsonl" => options.trace_jsonl = Some(path),
+                        _ => options.png = Some(path),
                     }
                 }
+                _ => return Err(Day09Error::InvalidArgument(arg)),
+            }
+        }
 
-                // Rope has moved into an L shaped pattern horizontally
-                // .....    .....    .....
-                // .....    .....    .....
-                // ..H.. -> ...H. -> ..TH.
-                // .T...    .T...    .....
-                // .....    .....    .....
-                if (curr_head.x - curr_tail.x).abs() == 2 && (curr_head.y - curr_tail.y).abs() == 1
-                {
-                    if curr_head.x > curr_tail.x {
-                        new_position = Some(Location {
-                            x: curr_head.x - 1,
-                            y: curr_head.y,
-                        });
-                    } else {
-                        new_position = Some(Location {
-                            x: curr_head.x + 1,
-                            y: curr_head.y,
-                        });
-                    }
-                }
+        // Only ropes in the plane can be drawn
+        if options.three_d && options.png.is_some() {
+            return Err(Day09Error::InvalidArgument("--png".to_string()));
+        }
 
-                // Rope has moved into a diagonal pattern
-                // .....    .....    .....
-                // .....    ...H.    ...H.
-                // ..H.. -> ..... -> ..T..
-                // .T...    .T...    .....
-                // .....    .....    .....
-                if (curr_head.x - curr_tail.x).abs() == 2 && (curr_head.y - curr_tail.y).abs() == 2
-                {
-                    if curr_head.x > curr_tail.x && curr_head.y > curr_tail.y {
-                        new_position = Some(Location {
-                            x: curr_head.x - 1,
-                            y: curr_head.y - 1,
-                        });
-                    } else if curr_head.x > curr_tail.x && curr_head.y < curr_tail.y {
-                        new_position = Some(Location {
-                            x: curr_head.x - 1,
-                            y: curr_head.y + 1,
-                        });
-                    } else if curr_head.x < curr_tail.x && curr_head.y > curr_tail.y {
-                        new_position = Some(Location {
-                            x: curr_head.x + 1,
-                            y: curr_head.y - 1,
-                        });
-                    } else if curr_head.x < curr_tail.x && curr_head.y < curr_tail.y {
-                        new_position = Some(Location {
-                            x: curr_head.x + 1,
-                            y: curr_head.y + 1,
-                        });
-                    }
-                }
+        Ok(options)
+    }
 
-                // If the (head, tail) pair is ever more than 2 segments away, one of the
-                // above simulation movements is wrong. Immediately dump the simualation
-                // state and panic
-                if (curr_head.x - curr_tail.x).abs() > 2 && (curr_head.y - curr_tail.y).abs() > 2 {
-                    println!("{rope:?}");
-                    println!("------------------ {index} ----------------");
-                    for y in 10..40 {
-                        for x in -40..40 {
-                            let curr = Location { x, y };
-                            /*
-                            if curr == Location::default() {
-                                print!("s");
-                            }
-                            */
-                            if let Some(index) = rope.iter().position(|&x| x == curr) {
-                                if index == 0 {
-                                    print!("H");
-                                } else if index == 10 {
-                                    print!("T");
-                                } else {
-                                    print!("{index}");
-                                }
-                            } else {
-                                print!(".");
-                            }
-                        }
-                        println!();
-                    }
+    /// Returns `true` if a trace of the rope was requested
+    fn tracing(&self) -> bool {
+        self.trace_csv.is_some() || self.trace_jsonl.is_some() || self.png.is_some()
+    }
+}
 
-                    panic!("Link is too far! Check the new conditions");
-                }
+/// Write the CSV and JSON lines exports of `trace` requested in `options`
+fn export_trace<P: Point>(trace: &Trace<P>, options: &Options) -> Result<(), Day09Error> {
+    if let Some(path) = &options.trace_csv {
+        trace.csv(create(path)?).map_err(Day09Error::Io)?;
+    }
 
-                // If any of the movement conditions are true, set the tail to the previous
-                // head position
-                if let Some(new_pos) = new_position {
-                    // Update this ropes position
-                    rope[index] = new_pos;
+    if let Some(path) = &options.trace_jsonl {
+        trace.jsonl(create(path)?).map_err(Day09Error::Io)?;
+    }
 
-                    // Solution only cares about the last tail positions
-                    if index == rope_length - 1 {
-                        tail_steps.insert(rope[index]);
-                    }
-                }
-            }
+    Ok(())
+}
+
+/// Usage: `day09 [--input FILE] [--knots N] [--3d] [--check-3d] [--trace-csv FILE|-]
+/// [--trace-jsonl FILE|-] [--png FILE] [--scale N]`
+///
+/// The motions are read from `--input FILE`, or the puzzle input. With `--knots`,
+/// simulate a single rope of `N` knots and print the number of locations visited by
+/// every knot. With `--3d`, the motions can also move `F`orward and `B`ackward along
+/// the z axis. `--check-3d` checks the 3D ropes give the same results as the 2D ropes
+/// on the motions. The trace exports record the rope of `N` knots (10 by default).
+fn main() -> Result<(), Day09Error> {
+    let options = Options::parse()?;
+    let knots = options.knots;
+
+    let input = match &options.input {
+        Some(path) => std::fs::read_to_string(path).map_err(Day09Error::Io)?,
+        None => INPUT.to_string(),
+    };
+
+    if options.check {
+        let motions = parse_motions::<Direction>(&input)?;
+        let motions_3d = parse_motions::<Direction3>(&input)?;
+        return check_3d(&motions, &motions_3d, knots.unwrap_or(10));
+    }
+
+    if options.three_d {
+        let motions = parse_motions(&input)?;
+        if options.tracing() {
+            let trace = Trace::<Location3>::record(&motions, knots.unwrap_or(10))?;
+            return export_trace(&trace, &options);
+        }
+
+        return report::<Location3>(&motions, knots);
+    }
 
-            // Update the head position
-            prev_rope[0] = rope[0];
+    let motions = parse_motions(&input)?;
+    if options.tracing() {
+        let trace = Trace::<Location>::record(&motions, knots.unwrap_or(10))?;
+        export_trace(&trace, &options)?;
+
+        if let Some(path) = &options.png {
+            trace.png(path, options.scale)?;
         }
+
+        return Ok(());
     }
 
-    // Return the number of steps moved by the tail
-    Ok(tail_steps.len())
+    report::<Location>(&motions, knots)
 }
 
-fn main() -> Result<(), Day09Error> {
-    let part1 = simulation(2)?;
-    println!("Part 1 tailed moved: {part1}");
-    let part2 = simulation(10)?;
-    println!("Part 2 tailed moved: {part2}");
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    Ok(())
+    #[test]
+    fn parse_diagonal_motions() {
+        // Motions read at runtime rather than the embedded puzzle input
+        let input = String::from("UR 4\nDL 1\n");
+        let motions = parse_motions::<Direction>(&input).unwrap();
+        assert_eq!(motions, [(Direction::UpRight, 4), (Direction::DownLeft, 1)]);
+
+        let rope = simulation::<Location>(&motions, 2).unwrap();
+        assert_eq!(rope.knots(), [Location { x: 3, y: -3 }; 2]);
+        assert_eq!(rope.tail_visited().len(), 4);
+
+        let input = String::from("U 1\nUX 2\n");
+        assert!(matches!(
+            parse_motions::<Direction>(&input),
+            Err(Day09Error::InvalidDirection(direction)) if direction == "UX"
+        ));
+    }
 }
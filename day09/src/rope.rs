@@ -0,0 +1,179 @@
+//! A rope of knots where every knot follows the knot ahead of it
+
+use std::collections::BTreeSet;
+
+use crate::Day09Error;
+
+/// A location a knot can be at, in any number of dimensions
+pub trait Point: Copy + Ord + Default + std::fmt::Display {
+    /// The directions the head of a rope can be moved in
+    type Direction: Copy;
+
+    /// The name of each axis of the location
+    const AXES: &'static [&'static str];
+
+    /// The coordinate along the axis `AXES[axis]`
+    fn coordinate(&self, axis: usize) -> i32;
+
+    /// Step the location by the given direction
+    fn step(&mut self, direction: Self::Direction);
+
+    /// Move one step towards `head` if it is no longer touching this location,
+    /// returning `true` if the location moved
+    fn follow(&mut self, head: Self) -> bool;
+}
+
+/// A direction that the rope can move in a 2-dimensional plane
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl TryFrom<&str> for Direction {
+    type Error = Day09Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "U" => Ok(Direction::Up),
+            "D" => Ok(Direction::Down),
+            "L" => Ok(Direction::Left),
+            "R" => Ok(Direction::Right),
+            "UL" => Ok(Direction::UpLeft),
+            "UR" => Ok(Direction::UpRight),
+            "DL" => Ok(Direction::DownLeft),
+            "DR" => Ok(Direction::DownRight),
+            _ => Err(Day09Error::InvalidDirection(value.to_string())),
+        }
+    }
+}
+
+/// A location on a 2-dimensional grid
+#[derive(Default, Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct Location {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl Point for Location {
+    type Direction = Direction;
+
+    const AXES: &'static [&'static str] = &["x", "y"];
+
+    fn coordinate(&self, axis: usize) -> i32 {
+        [self.x, self.y][axis]
+    }
+
+    /// Step the location by the given [`Direction`]
+    fn step(&mut self, direction: Direction) {
+        let (dx, dy) = match direction {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            Direction::UpLeft => (-1, -1),
+            Direction::UpRight => (1, -1),
+            Direction::DownLeft => (-1, 1),
+            Direction::DownRight => (1, 1),
+        };
+
+        self.x += dx;
+        self.y += dy;
+    }
+
+    /// Move one step towards `head` if it is no longer touching this location,
+    /// returning `true` if the location moved
+    ///
+    /// Each axis moves by the sign of the offset to the head, so the knot always ends
+    /// up closer to the head, whatever the offset was.
+    ///
+    /// ```text
+    /// .....    .....    .....
+    /// .....    ...H.    ...H.
+    /// ..H.. -> ..... -> ..T..
+    /// .T...    .T...    .....
+    /// .....    .....    .....
+    /// ```
+    fn follow(&mut self, head: Location) -> bool {
+        let (dx, dy) = (head.x - self.x, head.y - self.y);
+
+        // Touching knots, including diagonally and overlapping, don't move
+        if dx.abs() <= 1 && dy.abs() <= 1 {
+            return false;
+        }
+
+        self.x += dx.signum();
+        self.y += dy.signum();
+        true
+    }
+}
+
+/// A rope with a head knot and any number of knots following it
+#[derive(Debug, Clone)]
+pub struct Rope<P: Point = Location> {
+    /// The location of every knot, head first
+    knots: Vec<P>,
+
+    /// Every location visited by each knot, head first
+    visited: Vec<BTreeSet<P>>,
+}
+
+impl<P: Point> Rope<P> {
+    /// Create a rope of `knots` knots (including the head) all at the origin
+    pub fn new(knots: usize) -> Result<Self, Day09Error> {
+        if knots == 0 {
+            return Err(Day09Error::EmptyRope);
+        }
+
+        let start = P::default();
+        Ok(Self {
+            knots: vec![start; knots],
+            visited: vec![BTreeSet::from([start]); knots],
+        })
+    }
+
+    /// The location of every knot, head first
+    pub fn knots(&self) -> &[P] {
+        &self.knots
+    }
+
+    /// Every location visited by the knot at `index` (the head is 0), or `None` if
+    /// the rope doesn't have that many knots
+    pub fn visited(&self, index: usize) -> Option<&BTreeSet<P>> {
+        self.visited.get(index)
+    }
+
+    /// Every location visited by the last knot
+    pub fn tail_visited(&self) -> &BTreeSet<P> {
+        self.visited.last().expect("A rope always has a head")
+    }
+
+    /// Move the head one step in `direction` and let every other knot follow
+    pub fn step(&mut self, direction: P::Direction) {
+        self.knots[0].step(direction);
+        self.visited[0].insert(self.knots[0]);
+
+        // Treat each 2 chunk window of the rope as a (head, tail) pair
+        for index in 1..self.knots.len() {
+            let head = self.knots[index - 1];
+            if !self.knots[index].follow(head) {
+                // A knot that didn't move can't move the knots after it
+                break;
+            }
+
+            self.visited[index].insert(self.knots[index]);
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! Ropes moving through 3-dimensional space
+
+use crate::rope::{Location, Point};
+use crate::Day09Error;
+
+/// A direction that the rope can move in 3-dimensional space, one along each axis
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Direction3 {
+    Up,
+    Down,
+    Left,
+    Right,
+    Forward,
+    Backward,
+}
+
+impl TryFrom<&str> for Direction3 {
+    type Error = Day09Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "U" => Ok(Direction3::Up),
+            "D" => Ok(Direction3::Down),
+            "L" => Ok(Direction3::Left),
+            "R" => Ok(Direction3::Right),
+            "F" => Ok(Direction3::Forward),
+            "B" => Ok(Direction3::Backward),
+            _ => Err(Day09Error::InvalidDirection(value.to_string())),
+        }
+    }
+}
+
+/// A location in 3-dimensional space
+#[derive(Default, Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct Location3 {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl From<Location> for Location3 {
+    /// Place a location of the plane at `z = 0`
+    fn from(location: Location) -> Self {
+        Self {
+            x: location.x,
+            y: location.y,
+            z: 0,
+        }
+    }
+}
+
+impl std::fmt::Display for Location3 {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+impl Point for Location3 {
+    type Direction = Direction3;
+
+    const AXES: &'static [&'static str] = &["x", "y", "z"];
+
+    fn coordinate(&self, axis: usize) -> i32 {
+        [self.x, self.y, self.z][axis]
+    }
+
+    /// Step the location by the given [`Direction3`]. Up, down, left and right move in
+    /// the same plane as the 2-dimensional rope.
+    fn step(&mut self, direction: Direction3) {
+        match direction {
+            Direction3::Up => self.y -= 1,
+            Direction3::Down => self.y += 1,
+            Direction3::Left => self.x -= 1,
+            Direction3::Right => self.x += 1,
+            Direction3::Forward => self.z += 1,
+            Direction3::Backward => self.z -= 1,
+        }
+    }
+
+    /// Move one step towards `head` along each axis if it is no longer in the 26
+    /// locations around this one
+    fn follow(&mut self, head: Location3) -> bool {
+        let (dx, dy, dz) = (head.x - self.x, head.y - self.y, head.z - self.z);
+
+        if dx.abs() <= 1 && dy.abs() <= 1 && dz.abs() <= 1 {
+            return false;
+        }
+
+        self.x += dx.signum();
+        self.y += dy.signum();
+        self.z += dz.signum();
+        true
+    }
+}
@@ -0,0 +1,146 @@
+//! Record the location of every knot at every step and export it
+
+use std::collections::BTreeMap;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use aoc2022::image::{write_rgb_png, Rgb};
+
+use crate::rope::{Location, Point, Rope};
+use crate::Day09Error;
+
+/// The color of the cells visited by any knot but the tail
+const VISITED_COLOR: Rgb = [0x40, 0x40, 0x40];
+
+/// The color of the starting location
+const START_COLOR: Rgb = [0xff, 0xff, 0xff];
+
+/// The location of every knot of a rope after every step, starting with the initial
+/// locations
+#[derive(Debug, Clone)]
+pub struct Trace<P: Point> {
+    /// The number of knots in the rope
+    knots: usize,
+
+    /// The location of every knot (head first) for each step, one step after another
+    locations: Vec<P>,
+}
+
+impl<P: Point> Trace<P> {
+    /// Simulate a rope of `knots` knots through `motions`, recording every step
+    pub fn record(motions: &[(P::Direction, u32)], knots: usize) -> Result<Self, Day09Error> {
+        let mut rope = Rope::<P>::new(knots)?;
+        let mut locations = rope.knots().to_vec();
+
+        for &(direction, number_of_steps) in motions {
+            for _ in 0..number_of_steps {
+                rope.step(direction);
+                locations.extend_from_slice(rope.knots());
+            }
+        }
+
+        Ok(Self { knots, locations })
+    }
+
+    /// The location of every knot at each step, head first, starting with the initial
+    /// locations
+    pub fn steps(&self) -> impl ExactSizeIterator<Item = &[P]> {
+        self.locations.chunks_exact(self.knots)
+    }
+
+    /// Write one CSV row per knot per step
+    ///
+    /// ```text
+    /// step,knot,x,y
+    /// 0,0,0,0
+    /// ```
+    pub fn csv(&self, out: impl Write) -> std::io::Result<()> {
+        let mut out = BufWriter::new(out);
+        writeln!(out, "step,knot,{}", P::AXES.join(","))?;
+
+        for (step, knots) in self.steps().enumerate() {
+            for (knot, location) in knots.iter().enumerate() {
+                write!(out, "{step},{knot}")?;
+                for axis in 0..P::AXES.len() {
+                    write!(out, ",{}", location.coordinate(axis))?;
+                }
+                writeln!(out)?;
+            }
+        }
+
+        out.flush()
+    }
+
+    /// Write one JSON object per step with the coordinates of every knot, head first
+    ///
+    /// ```text
+    /// {"step":0,"knots":[[0,0],[0,0]]}
+    /// ```
+    pub fn jsonl(&self, out: impl Write) -> std::io::Result<()> {
+        let mut out = BufWriter::new(out);
+
+        for (step, knots) in self.steps().enumerate() {
+            let knots = knots
+                .iter()
+                .map(|location| {
+                    let coordinates = (0..P::AXES.len())
+                        .map(|axis| location.coordinate(axis).to_string())
+                        .collect::<Vec<_>>();
+                    format!("[{}]", coordinates.join(","))
+                })
+                .collect::<Vec<_>>();
+
+            writeln!(out, r#"{{"step":{step},"knots":[{}]}}"#, knots.join(","))?;
+        }
+
+        out.flush()
+    }
+}
+
+/// The blue to red color of `t` in `0.0..=1.0`
+fn time_color(t: f64) -> Rgb {
+    let red = (t * 255.0).round() as u8;
+    [red, 0x30, 0xff - red]
+}
+
+impl Trace<Location> {
+    /// Draw every cell visited by any knot, cropped to the visited area, with the
+    /// tail's path colored from blue to red by the time of its last visit. Each cell
+    /// is a `scale` by `scale` square.
+    pub fn png(&self, path: &Path, scale: usize) -> Result<(), Day09Error> {
+        let mut cells = BTreeMap::new();
+        let last_step = self.steps().len() - 1;
+
+        for (step, knots) in self.steps().enumerate() {
+            let (tail, rest) = knots.split_last().expect("A rope always has a head");
+            for location in rest {
+                cells.entry(*location).or_insert(VISITED_COLOR);
+            }
+
+            let t = if last_step == 0 {
+                1.0
+            } else {
+                step as f64 / last_step as f64
+            };
+            cells.insert(*tail, time_color(t));
+        }
+        cells.insert(Location::default(), START_COLOR);
+
+        // Crop the image to the visited cells
+        let min_x = cells.keys().map(|location| location.x).min().unwrap_or(0);
+        let max_x = cells.keys().map(|location| location.x).max().unwrap_or(0);
+        let min_y = cells.keys().map(|location| location.y).min().unwrap_or(0);
+        let max_y = cells.keys().map(|location| location.y).max().unwrap_or(0);
+        let width = (max_x - min_x + 1) as usize;
+        let height = (max_y - min_y + 1) as usize;
+
+        write_rgb_png(path, width, height, scale, |x, y| {
+            let location = Location {
+                x: min_x + x as i32,
+                y: min_y + y as i32,
+            };
+            cells.get(&location).copied().unwrap_or_default()
+        })
+        .map_err(Day09Error::Image)
+    }
+}
@@ -0,0 +1,10 @@
+; Compute 10! into x
+.include "math.s"
+
+    mov a, 1
+    mov b, TEN
+loop:                   ; a *= b for b in TEN..=1
+    mul a, b
+    sub b, 1
+    jnz b, loop
+    mov x, a
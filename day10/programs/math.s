@@ -0,0 +1,2 @@
+; Shared constants and helpers for the example programs
+.const TEN 10
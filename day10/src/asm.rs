@@ -0,0 +1,251 @@
+//! Two-pass assembler and disassembler for the handheld CPU
+//!
+//! ```text
+//! ; Comments run from `;` to the end of the line
+//! .include "lib.s"    ; Paste another file here, relative to this one
+//! .const TEN 10       ; Name a value
+//!
+//!     mov b, TEN
+//! loop:               ; Label the next instruction
+//!     mul a, b
+//!     sub b, 1
+//!     jnz b, loop     ; Jump to a label or an absolute `@address`
+//!     store a, [2]    ; Memory addresses are integers or constants in brackets
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::isa::{Instruction, Opcode, Operand, OperandKind, Register};
+use crate::Day10Error;
+
+/// A line of source with its comment removed, after includes are expanded
+struct SourceLine {
+    /// Where the line came from: `file:line`
+    location: String,
+
+    /// The trimmed text of the line
+    text: String,
+}
+
+/// Returns `true` if `name` can be used as a label or constant name
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Add the lines of `source` to `lines`, replacing each `.include` with the lines of
+/// the included file. `stack` holds the files currently being included.
+fn expand(
+    source: &str,
+    name: &str,
+    dir: &Path,
+    stack: &mut Vec<PathBuf>,
+    lines: &mut Vec<SourceLine>,
+) -> Result<(), Day10Error> {
+    for (index, line) in source.lines().enumerate() {
+        let location = format!("{name}:{}", index + 1);
+        let at = |err| Day10Error::At(location.clone(), Box::new(err));
+
+        // Remove the comment from the line
+        let text = line.split(';').next().unwrap_or_default().trim();
+        if text.is_empty() {
+            continue;
+        }
+
+        let Some(include) = text.strip_prefix(".include") else {
+            lines.push(SourceLine {
+                location,
+                text: text.to_string(),
+            });
+            continue;
+        };
+
+        let path = dir.join(include.trim().trim_matches('"'));
+        let path = path.canonicalize().map_err(|err| at(Day10Error::Io(err)))?;
+
+        // Including a file from itself would never end
+        if stack.contains(&path) {
+            return Err(at(Day10Error::IncludeCycle(path.display().to_string())));
+        }
+
+        let included = std::fs::read_to_string(&path).map_err(|err| at(Day10Error::Io(err)))?;
+        let included_dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+
+        stack.push(path.clone());
+        expand(
+            &included,
+            &path.display().to_string(),
+            &included_dir,
+            stack,
+            lines,
+        )?;
+        stack.pop();
+    }
+
+    Ok(())
+}
+
+/// Split a `label: instruction` line into the label (if any) and the rest of the line
+fn split_label(text: &str) -> (Option<&str>, &str) {
+    match text.split_once(':') {
+        Some((label, rest)) if is_identifier(label.trim()) => (Some(label.trim()), rest.trim()),
+        _ => (None, text),
+    }
+}
+
+/// Parse a single operand of the given `kind`
+fn parse_operand(
+    token: &str,
+    kind: OperandKind,
+    labels: &BTreeMap<&str, usize>,
+    constants: &BTreeMap<&str, i32>,
+) -> Result<Operand, Day10Error> {
+    let invalid = || Day10Error::InvalidArgument(token.to_string());
+
+    match (kind, Register::from_name(token)) {
+        (OperandKind::Register | OperandKind::Value, Some(register)) => {
+            Ok(Operand::Register(register))
+        }
+        (OperandKind::Register, None) => Err(invalid()),
+        (OperandKind::Value, None) => match constants.get(token) {
+            Some(value) => Ok(Operand::Immediate(*value)),
+            None => token
+                .parse::<i32>()
+                .map(Operand::Immediate)
+                .map_err(|_| invalid()),
+        },
+        (OperandKind::Address, _) => {
+            let address = token
+                .strip_prefix('[')
+                .and_then(|token| token.strip_suffix(']'))
+                .ok_or_else(invalid)?;
+
+            match constants.get(address) {
+                Some(value) => usize::try_from(*value).map_err(|_| invalid()),
+                None => address.parse::<usize>().map_err(|_| invalid()),
+            }
+            .map(Operand::Address)
+        }
+        (OperandKind::Target, _) => match token.strip_prefix('@') {
+            Some(address) => address
+                .parse::<usize>()
+                .map(Operand::Target)
+                .map_err(|_| invalid()),
+            None => labels
+                .get(token)
+                .map(|address| Operand::Target(*address))
+                .ok_or_else(|| Day10Error::UnknownLabel(token.to_string())),
+        },
+    }
+}
+
+/// Assemble `source`, where `name` names the source in errors and `.include` paths
+/// are relative to `dir`
+pub fn assemble(source: &str, name: &str, dir: &Path) -> Result<Vec<Instruction>, Day10Error> {
+    let mut lines = Vec::new();
+    expand(source, name, dir, &mut Vec::new(), &mut lines)?;
+
+    // First pass: find the address of every label and the value of every constant
+    let mut labels = BTreeMap::new();
+    let mut constants = BTreeMap::new();
+    let mut address = 0;
+    for SourceLine { location, text } in &lines {
+        let at = |err| Day10Error::At(location.clone(), Box::new(err));
+
+        if let Some(constant) = text.strip_prefix(".const") {
+            let (name, value) = constant
+                .trim()
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| at(Day10Error::InvalidInstructionFormat(text.clone())))?;
+
+            if !is_identifier(name) || Register::from_name(name).is_some() {
+                return Err(at(Day10Error::InvalidArgument(name.to_string())));
+            }
+
+            let value = value
+                .trim()
+                .parse::<i32>()
+                .map_err(|_| at(Day10Error::InvalidArgument(value.to_string())))?;
+
+            if constants.insert(name, value).is_some() {
+                return Err(at(Day10Error::DuplicateLabel(name.to_string())));
+            }
+
+            continue;
+        }
+
+        let (label, rest) = split_label(text);
+        if let Some(label) = label {
+            if labels.insert(label, address).is_some() {
+                return Err(at(Day10Error::DuplicateLabel(label.to_string())));
+            }
+        }
+
+        if !rest.is_empty() {
+            address += 1;
+        }
+    }
+
+    // Second pass: parse the instructions, resolving the labels and constants
+    let mut instructions = Vec::new();
+    for SourceLine { location, text } in &lines {
+        let at = |err| Day10Error::At(location.clone(), Box::new(err));
+
+        let (_, rest) = split_label(text);
+        if rest.is_empty() || text.starts_with(".const") {
+            continue;
+        }
+
+        let mut tokens = rest
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|token| !token.is_empty());
+
+        // Parse the opcode from the instruction
+        let mnemonic = tokens.next().unwrap_or_default();
+        let opcode = Opcode::from_mnemonic(mnemonic)
+            .ok_or_else(|| at(Day10Error::UnknownOpcode(mnemonic.to_string())))?;
+
+        let tokens = tokens.collect::<Vec<_>>();
+        let kinds = opcode.info().operands;
+        if tokens.len() != kinds.len() {
+            return Err(at(Day10Error::InvalidInstructionFormat(rest.to_string())));
+        }
+
+        let operands = tokens
+            .into_iter()
+            .zip(kinds)
+            .map(|(token, kind)| parse_operand(token, *kind, &labels, &constants))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(at)?;
+
+        instructions.push(Instruction::new(opcode, &operands).map_err(at)?);
+    }
+
+    Ok(instructions)
+}
+
+/// Assemble the file at `path`
+pub fn assemble_file(path: &Path) -> Result<Vec<Instruction>, Day10Error> {
+    let source = std::fs::read_to_string(path).map_err(Day10Error::Io)?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    assemble(&source, &path.display().to_string(), dir)
+}
+
+/// List the `instructions` with their addresses, jump targets as absolute addresses
+///
+/// ```text
+/// 0000: mov a 1
+/// 0001: mul a b
+/// 0002: jnz b @1
+/// ```
+pub fn disassemble(instructions: &[Instruction]) -> String {
+    instructions
+        .iter()
+        .enumerate()
+        .map(|(address, instruction)| format!("{address:04}: {instruction}\n"))
+        .collect()
+}
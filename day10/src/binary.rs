@@ -0,0 +1,130 @@
+//! Compact binary encoding of programs, so they load without parsing text
+//!
+//! A program is the [`MAGIC`] bytes, the number of instructions as a little endian
+//! `u32`, then each instruction: the opcode's index in [`Opcode::ALL`] followed by
+//! each operand as its declared kind:
+//!
+//! - Register: the register's index in [`Register::ALL`]
+//! - Value: `0` and a register index, or `1` and a little endian `i32`
+//! - Target: a little endian `u32` address
+//! - Address: a little endian `u32` memory address
+
+use crate::isa::{Instruction, Opcode, Operand, OperandKind, Register};
+use crate::Day10Error;
+
+/// The bytes every encoded program starts with
+pub const MAGIC: &[u8; 4] = b"HCPU";
+
+/// Encode `instructions` into the binary format
+pub fn encode(instructions: &[Instruction]) -> Result<Vec<u8>, Day10Error> {
+    let too_large = || Day10Error::InvalidBinary("program too large to encode");
+
+    let mut bytes = MAGIC.to_vec();
+    let count = u32::try_from(instructions.len()).map_err(|_| too_large())?;
+    bytes.extend(count.to_le_bytes());
+
+    for instruction in instructions {
+        bytes.push(instruction.opcode() as u8);
+
+        let kinds = instruction.opcode().info().operands;
+        for (operand, kind) in instruction.operands().iter().zip(kinds) {
+            match (kind, operand) {
+                (OperandKind::Register, Operand::Register(register)) => {
+                    bytes.push(*register as u8);
+                }
+                (OperandKind::Value, Operand::Register(register)) => {
+                    bytes.extend([0, *register as u8]);
+                }
+                (OperandKind::Value, Operand::Immediate(value)) => {
+                    bytes.push(1);
+                    bytes.extend(value.to_le_bytes());
+                }
+                (OperandKind::Target, Operand::Target(address))
+                | (OperandKind::Address, Operand::Address(address)) => {
+                    let address = u32::try_from(*address).map_err(|_| too_large())?;
+                    bytes.extend(address.to_le_bytes());
+                }
+                _ => unreachable!("Operand kinds checked by Instruction::new"),
+            }
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// Reads the fields of an encoded program in order
+struct Reader<'a> {
+    /// The bytes not read yet
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    /// Read the next `N` bytes
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], Day10Error> {
+        let (head, rest) = self
+            .bytes
+            .split_first_chunk::<N>()
+            .ok_or(Day10Error::InvalidBinary("unexpected end of program"))?;
+
+        self.bytes = rest;
+        Ok(*head)
+    }
+
+    /// Read a register index
+    fn register(&mut self) -> Result<Register, Day10Error> {
+        let [index] = self.take()?;
+        Register::ALL
+            .get(usize::from(index))
+            .copied()
+            .ok_or(Day10Error::InvalidBinary("unknown register"))
+    }
+
+    /// Read a little endian `u32`
+    fn u32(&mut self) -> Result<u32, Day10Error> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+}
+
+/// Decode a program encoded by [`encode`]
+pub fn decode(bytes: &[u8]) -> Result<Vec<Instruction>, Day10Error> {
+    let mut reader = Reader { bytes };
+
+    if &reader.take::<4>()? != MAGIC {
+        return Err(Day10Error::InvalidBinary("missing magic bytes"));
+    }
+
+    let count = reader.u32()?;
+    let mut instructions = Vec::new();
+    for _ in 0..count {
+        let [index] = reader.take()?;
+        let opcode = *Opcode::ALL
+            .get(usize::from(index))
+            .ok_or(Day10Error::InvalidBinary("unknown opcode"))?;
+
+        let mut operands = Vec::new();
+        for kind in opcode.info().operands {
+            let operand = match kind {
+                OperandKind::Register => Operand::Register(reader.register()?),
+                OperandKind::Value => match reader.take()? {
+                    [0] => Operand::Register(reader.register()?),
+                    [1] => Operand::Immediate(i32::from_le_bytes(reader.take()?)),
+                    _ => return Err(Day10Error::InvalidBinary("unknown value tag")),
+                },
+                OperandKind::Target => Operand::Target(reader.u32()? as usize),
+                OperandKind::Address => Operand::Address(reader.u32()? as usize),
+            };
+
+            operands.push(operand);
+        }
+
+        instructions.push(Instruction::new(opcode, &operands)?);
+    }
+
+    if !reader.bytes.is_empty() {
+        return Err(Day10Error::InvalidBinary(
+            "trailing bytes after the program",
+        ));
+    }
+
+    Ok(instructions)
+}
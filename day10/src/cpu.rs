@@ -0,0 +1,217 @@
+//! The execution unit of the handheld device
+
+use crate::isa::{Flow, Instruction, Memory, Register, Registers, MEMORY_SIZE};
+use crate::Day10Error;
+
+/// A device attached to the CPU bus. Devices see the CPU state, including the memory
+/// they are mapped to, during every cycle, before the instruction finishing in that
+/// cycle changes it.
+pub trait Device {
+    /// Run one clock cycle of the device
+    fn tick(&mut self, cpu: &Cpu);
+}
+
+/// The execution unit of the processor for Day 10
+pub struct Cpu {
+    /// The current instructions in the CPU
+    instructions: Vec<Instruction>,
+
+    /// The register state of the CPU
+    registers: Registers,
+
+    /// The memory, which devices are controlled through
+    memory: Memory,
+
+    /// The currently executing instruction if it takes longer than 1 clock cycle
+    pipeline: Option<Pipeline>,
+
+    /// The instruction pointer
+    ip: usize,
+
+    /// Number of cycles executed
+    cycles_executed: usize,
+
+    /// The devices attached to the bus, ticked every cycle
+    devices: Vec<Box<dyn Device>>,
+}
+
+/// Is the CPU continuing to execute or is it finished
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Execution {
+    Continue,
+    Finished,
+}
+
+impl Cpu {
+    /// Create a CPU ready to execute `instructions` from the first one
+    pub fn new(instructions: Vec<Instruction>) -> Self {
+        // Init the register state
+        let mut registers = [0; Register::COUNT];
+        registers[Register::X as usize] = 1;
+
+        Cpu {
+            instructions,
+            registers,
+            memory: [0; MEMORY_SIZE],
+            pipeline: None,
+            ip: 0,
+
+            // The problem starts cycles at 1. Use this value here to calculate the
+            // correct cycles when checking for an answer
+            cycles_executed: 1,
+            devices: Vec::new(),
+        }
+    }
+
+    /// Attach `device` to the bus, ticking it every cycle from now on
+    pub fn attach(&mut self, device: impl Device + 'static) {
+        self.devices.push(Box::new(device));
+    }
+
+    /// The instructions loaded in the CPU
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// The address of the next instruction to fetch
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    /// The current value of `register`
+    pub fn register(&self, register: Register) -> i32 {
+        self.registers[register as usize]
+    }
+
+    /// The word of memory at `address`
+    pub fn load(&self, address: usize) -> i32 {
+        self.memory[address]
+    }
+
+    /// The number of cycles executed, starting at 1
+    pub fn cycles_executed(&self) -> usize {
+        self.cycles_executed
+    }
+
+    /// Returns `true` if there is nothing left to execute
+    pub fn is_finished(&self) -> bool {
+        self.pipeline.is_none() && self.ip >= self.instructions.len()
+    }
+
+    /// Apply a given instruction to the current CPU state
+    pub fn execute(&mut self, instruction: Instruction) {
+        if let Flow::Jump(target) = instruction.execute(&mut self.registers, &mut self.memory) {
+            self.ip = target;
+        }
+    }
+
+    /// Step the CPU one clock cycle
+    pub fn step(&mut self) -> Result<Execution, Day10Error> {
+        // Grab the next instruction if there isn't one already executing
+        if self.pipeline.is_none() {
+            // If there are no more instructions to execute, the CPU is finished
+            if self.ip >= self.instructions.len() {
+                return Ok(Execution::Finished);
+            }
+
+            // Fetch the next instruction
+            let instr = self.instructions[self.ip];
+
+            // Increment the instruction pointer
+            self.ip += 1;
+
+            // Set the instruction into the pipeline
+            self.pipeline = Some(Pipeline {
+                instruction: instr,
+                cycles_left: instr.cycles(),
+            });
+        }
+
+        // Let the devices see the state during this cycle. They are taken out of the
+        // CPU so they can borrow it.
+        let mut devices = std::mem::take(&mut self.devices);
+        for device in &mut devices {
+            device.tick(self);
+        }
+        self.devices = devices;
+
+        // Each step call will always step the CPU cycles
+        self.cycles_executed += 1;
+
+        // Check if there is already an existing instruction executing in this pipeline
+        let Some(Pipeline {
+            instruction,
+            mut cycles_left,
+        }) = self.pipeline.take()
+        else {
+            return Err(Day10Error::InstructionNotLoaded);
+        };
+
+        // Reduce the number of cycles left for this instruction by 1
+        cycles_left -= 1;
+
+        // If this instruction has finished, set the instruction to execute as this
+        // one and reset the pipeline
+        if cycles_left == 0 {
+            self.execute(instruction);
+        } else {
+            // There are still cycles left to execute this instruction, so we can't
+            // execute it yet. Set the pipeline back and continue.
+            self.pipeline = Some(Pipeline {
+                instruction,
+                cycles_left,
+            });
+        }
+
+        // CPU still has instructions to execute, continue
+        Ok(Execution::Continue)
+    }
+
+    /// Describe the instruction in the pipeline and how many cycles it has left
+    pub fn pipeline(&self) -> String {
+        match self.pipeline {
+            Some(Pipeline {
+                instruction,
+                cycles_left,
+            }) => format!("{instruction} ({cycles_left} cycles left)"),
+            None => "empty".to_string(),
+        }
+    }
+
+    /// Describe the CPU state with the next `upcoming` instructions to fetch
+    pub fn state(&self, upcoming: usize) -> String {
+        let mut state = format!("------- Cycle {:03} -------\n", self.cycles_executed);
+        for register in Register::ALL {
+            state.push_str(&format!(
+                "{:>2}: {:4}\n",
+                register.name().to_uppercase(),
+                self.register(register)
+            ));
+        }
+        state.push_str(&format!("IP: {:4}\n", self.ip));
+        state.push_str("--- Memory ---\n");
+        for (address, words) in self.memory.chunks(8).enumerate() {
+            let words = words
+                .iter()
+                .map(|word| format!("{word:4}"))
+                .collect::<Vec<_>>();
+            state.push_str(&format!("[{:02}] {}\n", address * 8, words.join(" ")));
+        }
+        state.push_str("--- Pipeline ---\n");
+        state.push_str(&format!("{}\n", self.pipeline()));
+        state.push_str("--- Instrs ---\n");
+        for ip in self.ip..(self.ip + upcoming).min(self.instructions.len()) {
+            state.push_str(&format!("{ip:4}: {}\n", self.instructions[ip]));
+        }
+
+        state
+    }
+}
+
+/// A particular pipeline in the CPU that can execute opcodes for a given number of
+/// instructions
+#[derive(Debug, Copy, Clone)]
+struct Pipeline {
+    instruction: Instruction,
+    cycles_left: u32,
+}
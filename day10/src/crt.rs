@@ -0,0 +1,176 @@
+//! The CRT, a display device on the CPU bus that draws the sprite under its beam
+//!
+//! The CRT is mapped to two words of memory, which programs `store` to:
+//!
+//! - [`Crt::CONTROL`]: while zero, the sprite follows the `x` register as in the puzzle.
+//!   Otherwise the sprite is placed from memory.
+//! - [`Crt::SPRITE`]: the column of the sprite's center when placed from memory
+
+use crate::cpu::{Cpu, Device};
+use crate::isa::Register;
+use crate::Day10Error;
+
+/// The pixels drawn by the [`Crt`] during one refresh
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// The width in pixels
+    width: usize,
+
+    /// The height in pixels
+    height: usize,
+
+    /// Every pixel row by row, `true` when lit
+    pixels: Vec<bool>,
+
+    /// The index of the next pixel the beam draws, `width * height` once the frame
+    /// is complete
+    beam: usize,
+}
+
+impl Frame {
+    /// An unlit frame of `width` by `height` pixels, with the beam at the top left
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![false; width * height],
+            beam: 0,
+        }
+    }
+
+    /// The width in pixels
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height in pixels
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The index of the next pixel the beam draws, in reading order
+    pub fn beam(&self) -> usize {
+        self.beam
+    }
+
+    /// Returns `true` once the beam has drawn every pixel
+    pub fn is_complete(&self) -> bool {
+        self.beam == self.pixels.len()
+    }
+
+    /// Returns `true` if the pixel at column `x` of row `y` is lit
+    pub fn is_lit(&self, x: usize, y: usize) -> bool {
+        self.pixels[y * self.width + x]
+    }
+
+    /// The pixels of every row, `true` when lit
+    pub fn rows(&self) -> Vec<&[bool]> {
+        self.pixels.chunks(self.width).collect()
+    }
+}
+
+impl std::fmt::Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for row in self.rows() {
+            for pixel in row {
+                write!(f, "{}", if *pixel { '#' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A callback given the frames of the [`Crt`]
+type FrameCallback = Box<dyn FnMut(&Frame)>;
+
+/// A display drawing one pixel per cycle, row by row. A pixel is lit if the sprite,
+/// centered on the column in the `x` register or in its mapped memory, covers the
+/// pixel's column.
+pub struct Crt {
+    /// The frame being drawn
+    frame: Frame,
+
+    /// The width of the sprite in pixels
+    sprite_width: usize,
+
+    /// Called with every completed frame, and with the frame being drawn every
+    /// `scan_interval` pixels
+    on_frame: Option<FrameCallback>,
+
+    /// The number of pixels between calls of `on_frame` during a refresh, or only
+    /// after each refresh if `None`
+    scan_interval: Option<usize>,
+}
+
+impl Crt {
+    /// The width of the puzzle's display
+    pub const WIDTH: usize = 40;
+
+    /// The height of the puzzle's display
+    pub const HEIGHT: usize = 6;
+
+    /// The width of the puzzle's sprite
+    pub const SPRITE_WIDTH: usize = 3;
+
+    /// The memory address of the control word: non-zero to place the sprite from
+    /// [`Crt::SPRITE`] instead of the `x` register
+    pub const CONTROL: usize = 0;
+
+    /// The memory address of the sprite's column when [`Crt::CONTROL`] is set
+    pub const SPRITE: usize = 1;
+
+    /// A display of `width` by `height` pixels with a sprite `sprite_width` pixels wide
+    pub fn new(width: usize, height: usize, sprite_width: usize) -> Result<Self, Day10Error> {
+        if width == 0 || height == 0 || sprite_width == 0 {
+            return Err(Day10Error::InvalidCrtSize(width, height, sprite_width));
+        }
+
+        Ok(Self {
+            frame: Frame::new(width, height),
+            sprite_width,
+            on_frame: None,
+            scan_interval: None,
+        })
+    }
+
+    /// Call `on_frame` with every completed frame
+    pub fn set_on_frame(&mut self, on_frame: impl FnMut(&Frame) + 'static) {
+        self.on_frame = Some(Box::new(on_frame));
+    }
+
+    /// Also call the frame callback with the frame being drawn every `pixels` pixels,
+    /// to watch the beam scan
+    pub fn set_scan_interval(&mut self, pixels: usize) {
+        self.scan_interval = Some(pixels.max(1));
+    }
+}
+
+impl Device for Crt {
+    fn tick(&mut self, cpu: &Cpu) {
+        // Start the next refresh over the previous frame
+        if self.frame.is_complete() {
+            self.frame.beam = 0;
+        }
+
+        let column = (self.frame.beam % self.frame.width) as i64;
+        let x = if cpu.load(Self::CONTROL) == 0 {
+            i64::from(cpu.register(Register::X))
+        } else {
+            i64::from(cpu.load(Self::SPRITE))
+        };
+        let sprite = x - (self.sprite_width as i64 - 1) / 2..=x + self.sprite_width as i64 / 2;
+        self.frame.pixels[self.frame.beam] = sprite.contains(&column);
+        self.frame.beam += 1;
+
+        let scanned = self
+            .scan_interval
+            .is_some_and(|interval| self.frame.beam.is_multiple_of(interval));
+        if self.frame.is_complete() || scanned {
+            if let Some(on_frame) = &mut self.on_frame {
+                on_frame(&self.frame);
+            }
+        }
+    }
+}
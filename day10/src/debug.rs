@@ -0,0 +1,362 @@
+//! Debugger for the handheld CPU with breakpoints, watches and cycle tracing
+
+use std::io::{BufRead, Write};
+
+use crate::cpu::{Cpu, Execution};
+use crate::expr::Expr;
+use crate::isa::Register;
+use crate::Day10Error;
+
+/// A condition that stops [`Debugger::run`] at the start of a cycle
+#[derive(Debug, Clone)]
+pub enum Breakpoint {
+    /// Stop at the start of the given cycle
+    Cycle(usize),
+
+    /// Stop when the next instruction to fetch is at the given address
+    Ip(usize),
+
+    /// Stop when the expression is true
+    When(String, Expr),
+}
+
+impl Breakpoint {
+    /// Returns `true` if the breakpoint stops the CPU in its current state
+    fn hit(&self, cpu: &Cpu) -> Result<bool, Day10Error> {
+        Ok(match self {
+            Breakpoint::Cycle(cycle) => cpu.cycles_executed() == *cycle,
+            Breakpoint::Ip(ip) => cpu.ip() == *ip,
+            Breakpoint::When(_, expr) => expr.eval(cpu)? != 0,
+        })
+    }
+}
+
+impl std::fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Breakpoint::Cycle(cycle) => write!(f, "cycle {cycle}"),
+            Breakpoint::Ip(ip) => write!(f, "ip {ip}"),
+            Breakpoint::When(source, _) => write!(f, "if {source}"),
+        }
+    }
+}
+
+/// An expression sampled at the start of every cycle where its condition is true
+#[derive(Debug, Clone)]
+pub struct Watch {
+    /// The text of the expression
+    source: String,
+
+    /// The sampled expression
+    expr: Expr,
+
+    /// Only sample when this expression (with its text) is true, or every cycle if
+    /// `None`
+    when: Option<(String, Expr)>,
+
+    /// The cycle and value of every sample
+    samples: Vec<(usize, i64)>,
+}
+
+impl Watch {
+    /// Watch the expression `source`, sampled whenever the expression `when` is true
+    pub fn new(source: &str, when: Option<&str>) -> Result<Self, Day10Error> {
+        Ok(Self {
+            source: source.to_string(),
+            expr: Expr::parse(source)?,
+            when: when
+                .map(|when| Ok::<_, Day10Error>((when.to_string(), Expr::parse(when)?)))
+                .transpose()?,
+            samples: Vec::new(),
+        })
+    }
+
+    /// The cycle and value of every sample so far
+    pub fn samples(&self) -> &[(usize, i64)] {
+        &self.samples
+    }
+
+    /// The sum of every sample so far
+    pub fn sum(&self) -> i64 {
+        self.samples.iter().map(|(_, value)| value).sum()
+    }
+
+    /// Sample the expression if its condition holds for the current state of `cpu`
+    fn sample(&mut self, cpu: &Cpu) -> Result<(), Day10Error> {
+        if let Some((_, when)) = &self.when {
+            if when.eval(cpu)? == 0 {
+                return Ok(());
+            }
+        }
+
+        self.samples
+            .push((cpu.cycles_executed(), self.expr.eval(cpu)?));
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Watch {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.source)?;
+        if let Some((when, _)) = &self.when {
+            write!(f, " when {when}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Why [`Debugger::run`] stopped
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Stop {
+    /// The breakpoint with the given index was hit
+    Breakpoint(usize),
+
+    /// The program finished
+    Finished,
+}
+
+/// Runs a [`Cpu`] one cycle at a time, checking breakpoints and sampling watches
+pub struct Debugger {
+    /// The CPU being debugged
+    cpu: Cpu,
+
+    /// The breakpoints, `None` once deleted so the indexes of the others don't change
+    breakpoints: Vec<Option<Breakpoint>>,
+
+    /// The watches
+    watches: Vec<Watch>,
+
+    /// Where to log the state at the start of every cycle
+    trace: Option<Box<dyn Write>>,
+}
+
+impl Debugger {
+    /// Debug `cpu`
+    pub fn new(cpu: Cpu) -> Self {
+        Self {
+            cpu,
+            breakpoints: Vec::new(),
+            watches: Vec::new(),
+            trace: None,
+        }
+    }
+
+    /// The CPU being debugged
+    pub fn cpu(&self) -> &Cpu {
+        &self.cpu
+    }
+
+    /// Add a breakpoint, returning its index
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> usize {
+        self.breakpoints.push(Some(breakpoint));
+        self.breakpoints.len() - 1
+    }
+
+    /// Delete the breakpoint at `index`, returning it
+    pub fn delete_breakpoint(&mut self, index: usize) -> Option<Breakpoint> {
+        self.breakpoints.get_mut(index)?.take()
+    }
+
+    /// Add a watch, returning its index
+    pub fn add_watch(&mut self, watch: Watch) -> usize {
+        self.watches.push(watch);
+        self.watches.len() - 1
+    }
+
+    /// Every watch, in the order they were added
+    pub fn watches(&self) -> &[Watch] {
+        &self.watches
+    }
+
+    /// Log the state of the CPU at the start of every cycle to `trace`
+    pub fn set_trace(&mut self, trace: Box<dyn Write>) {
+        self.trace = Some(trace);
+    }
+
+    /// Step the CPU one clock cycle, sampling the watches and logging the trace for
+    /// the state at the start of the cycle
+    pub fn step(&mut self) -> Result<Execution, Day10Error> {
+        if self.cpu.is_finished() {
+            return Ok(Execution::Finished);
+        }
+
+        for watch in &mut self.watches {
+            watch.sample(&self.cpu)?;
+        }
+
+        if let Some(trace) = &mut self.trace {
+            let cpu = &self.cpu;
+            write!(trace, "cycle={} ip={}", cpu.cycles_executed(), cpu.ip())
+                .map_err(Day10Error::Io)?;
+            for register in Register::ALL {
+                write!(trace, " {}={}", register.name(), cpu.register(register))
+                    .map_err(Day10Error::Io)?;
+            }
+            writeln!(trace, " pipeline={:?}", cpu.pipeline()).map_err(Day10Error::Io)?;
+        }
+
+        self.cpu.step()
+    }
+
+    /// Run until a breakpoint is hit or the program finishes. At least one cycle is
+    /// executed, so running again from a breakpoint moves on.
+    pub fn run(&mut self) -> Result<Stop, Day10Error> {
+        loop {
+            if self.step()? == Execution::Finished {
+                return Ok(Stop::Finished);
+            }
+
+            for (index, breakpoint) in self.breakpoints.iter().enumerate() {
+                if let Some(breakpoint) = breakpoint {
+                    if breakpoint.hit(&self.cpu)? {
+                        return Ok(Stop::Breakpoint(index));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Flush the trace log
+    pub fn flush(&mut self) -> Result<(), Day10Error> {
+        match &mut self.trace {
+            Some(trace) => trace.flush().map_err(Day10Error::Io),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The commands of the interactive debugger
+const HELP: &str = "\
+step [N]                 Execute N cycles (default 1) and show the state
+continue                 Run until a breakpoint is hit or the program finishes
+break cycle N            Stop at the start of cycle N
+break ip N               Stop when the next instruction is at address N
+break if EXPR            Stop when EXPR is true
+delete N                 Delete breakpoint N
+watch EXPR [when COND]   Sample EXPR at the start of every cycle where COND is true
+watches                  Show the samples and sum of every watch
+print EXPR               Evaluate EXPR on the current state
+info                     Show the registers, the pipeline and the next instructions
+trace FILE               Log the state at the start of every cycle to FILE
+quit                     Exit the debugger
+";
+
+/// Execute one debugger command, returning `false` to quit
+fn command(debugger: &mut Debugger, line: &str, out: &mut impl Write) -> Result<bool, Day10Error> {
+    let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+    let args = args.trim();
+    let number = |value: &str| {
+        value
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| Day10Error::InvalidCommandLine(line.to_string()))
+    };
+
+    match command {
+        "s" | "step" => {
+            let count = if args.is_empty() { 1 } else { number(args)? };
+            for _ in 0..count {
+                if debugger.step()? == Execution::Finished {
+                    writeln!(out, "Finished").map_err(Day10Error::Io)?;
+                    break;
+                }
+            }
+
+            write!(out, "{}", debugger.cpu().state(3)).map_err(Day10Error::Io)?;
+        }
+        "c" | "continue" => match debugger.run()? {
+            Stop::Breakpoint(index) => writeln!(
+                out,
+                "Breakpoint {index} hit at cycle {}",
+                debugger.cpu().cycles_executed()
+            )
+            .map_err(Day10Error::Io)?,
+            Stop::Finished => writeln!(
+                out,
+                "Finished after {} cycles",
+                debugger.cpu().cycles_executed() - 1
+            )
+            .map_err(Day10Error::Io)?,
+        },
+        "b" | "break" => {
+            let breakpoint = match args.split_once(' ') {
+                Some(("cycle", cycle)) => Breakpoint::Cycle(number(cycle)?),
+                Some(("ip", ip)) => Breakpoint::Ip(number(ip)?),
+                Some(("if", expr)) => Breakpoint::When(expr.to_string(), Expr::parse(expr)?),
+                _ => return Err(Day10Error::InvalidCommandLine(line.to_string())),
+            };
+
+            let description = breakpoint.to_string();
+            let index = debugger.add_breakpoint(breakpoint);
+            writeln!(out, "Breakpoint {index}: {description}").map_err(Day10Error::Io)?;
+        }
+        "delete" => match debugger.delete_breakpoint(number(args)?) {
+            Some(breakpoint) => {
+                writeln!(out, "Deleted breakpoint {args}: {breakpoint}").map_err(Day10Error::Io)?;
+            }
+            None => writeln!(out, "No breakpoint {args}").map_err(Day10Error::Io)?,
+        },
+        "w" | "watch" => {
+            let (expr, when) = match args.split_once(" when ") {
+                Some((expr, when)) => (expr, Some(when)),
+                None => (args, None),
+            };
+
+            let index = debugger.add_watch(Watch::new(expr, when)?);
+            writeln!(out, "Watch {index}: {}", debugger.watches()[index])
+                .map_err(Day10Error::Io)?;
+        }
+        "watches" => {
+            for (index, watch) in debugger.watches().iter().enumerate() {
+                writeln!(out, "Watch {index}: {watch}").map_err(Day10Error::Io)?;
+                for (cycle, value) in watch.samples() {
+                    writeln!(out, "  cycle {cycle}: {value}").map_err(Day10Error::Io)?;
+                }
+                writeln!(out, "  sum: {}", watch.sum()).map_err(Day10Error::Io)?;
+            }
+        }
+        "p" | "print" => {
+            let value = Expr::parse(args)?.eval(debugger.cpu())?;
+            writeln!(out, "{value}").map_err(Day10Error::Io)?;
+        }
+        "i" | "info" => write!(out, "{}", debugger.cpu().state(5)).map_err(Day10Error::Io)?,
+        "trace" => {
+            let file = std::fs::File::create(args).map_err(Day10Error::Io)?;
+            debugger.set_trace(Box::new(std::io::BufWriter::new(file)));
+        }
+        "q" | "quit" => return Ok(false),
+        "h" | "help" => write!(out, "{HELP}").map_err(Day10Error::Io)?,
+        _ => return Err(Day10Error::InvalidCommandLine(line.to_string())),
+    }
+
+    Ok(true)
+}
+
+/// Read debugger commands from `input` until `quit` or the end of the input,
+/// printing a prompt to stderr and the results to `out`. A failed command is
+/// reported and doesn't end the session.
+pub fn repl(
+    debugger: &mut Debugger,
+    input: impl BufRead,
+    mut out: impl Write,
+) -> Result<(), Day10Error> {
+    eprint!("(hdb) ");
+    for line in input.lines() {
+        let line = line.map_err(Day10Error::Io)?;
+        let line = line.trim();
+
+        if !line.is_empty() {
+            match command(debugger, line, &mut out) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(err) => writeln!(out, "Error: {err}").map_err(Day10Error::Io)?,
+            }
+        }
+
+        out.flush().map_err(Day10Error::Io)?;
+        eprint!("(hdb) ");
+    }
+
+    debugger.flush()
+}
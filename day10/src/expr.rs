@@ -0,0 +1,233 @@
+//! Expressions over the CPU state, used by the debugger's breakpoints and watches
+//!
+//! ```text
+//! cycle * x
+//! cycle % 40 == 20 && cycle <= 220
+//! ip == 4 || (a > 10 && b != 0)
+//! ```
+//!
+//! The variables are `cycle`, `ip` and every register. Comparisons and logical
+//! operators give `1` for true and `0` for false, and any non-zero value is true.
+
+use crate::cpu::Cpu;
+use crate::isa::Register;
+use crate::Day10Error;
+
+/// A binary operator
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BinaryOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl BinaryOp {
+    /// Every operator with its token and precedence, longest tokens first so `<=`
+    /// isn't read as `<`
+    const TOKENS: [(&'static str, BinaryOp, u8); 13] = [
+        ("||", BinaryOp::Or, 1),
+        ("&&", BinaryOp::And, 2),
+        ("==", BinaryOp::Eq, 3),
+        ("!=", BinaryOp::Ne, 3),
+        ("<=", BinaryOp::Le, 4),
+        (">=", BinaryOp::Ge, 4),
+        ("<", BinaryOp::Lt, 4),
+        (">", BinaryOp::Gt, 4),
+        ("+", BinaryOp::Add, 5),
+        ("-", BinaryOp::Sub, 5),
+        ("*", BinaryOp::Mul, 6),
+        ("/", BinaryOp::Div, 6),
+        ("%", BinaryOp::Rem, 6),
+    ];
+}
+
+/// A variable of the CPU state
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Variable {
+    /// The current cycle, starting at 1
+    Cycle,
+
+    /// The instruction pointer
+    Ip,
+
+    /// A register
+    Register(Register),
+}
+
+/// A parsed expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    /// An integer literal
+    Literal(i64),
+
+    /// The value of a variable of the CPU state
+    Variable(Variable),
+
+    /// The negation of an expression
+    Neg(Box<Expr>),
+
+    /// The logical not of an expression
+    Not(Box<Expr>),
+
+    /// A binary operation
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+/// Parses an expression by precedence climbing
+struct Parser<'a> {
+    /// The full expression, for errors
+    source: &'a str,
+
+    /// The rest of the expression not parsed yet
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    /// The error for an invalid expression
+    fn error(&self) -> Day10Error {
+        Day10Error::InvalidExpression(self.source.to_string())
+    }
+
+    /// Skip whitespace and return `true` if the rest starts with `token`, consuming it
+    fn eat(&mut self, token: &str) -> bool {
+        self.rest = self.rest.trim_start();
+        match self.rest.strip_prefix(token) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Parse the binary operations with at least `min_precedence`
+    fn binary(&mut self, min_precedence: u8) -> Result<Expr, Day10Error> {
+        let mut left = self.unary()?;
+
+        loop {
+            self.rest = self.rest.trim_start();
+            let Some((token, op, precedence)) = BinaryOp::TOKENS
+                .into_iter()
+                .find(|(token, _, _)| self.rest.starts_with(token))
+            else {
+                return Ok(left);
+            };
+
+            if precedence < min_precedence {
+                return Ok(left);
+            }
+
+            self.rest = &self.rest[token.len()..];
+
+            // Every operator is left associative
+            let right = self.binary(precedence + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    /// Parse a unary operation, a parenthesized expression, a literal or a variable
+    fn unary(&mut self) -> Result<Expr, Day10Error> {
+        if self.eat("-") {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+
+        if self.eat("(") {
+            let expr = self.binary(0)?;
+            if !self.eat(")") {
+                return Err(self.error());
+            }
+
+            return Ok(expr);
+        }
+
+        let len = self
+            .rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(self.rest.len());
+        let (word, rest) = self.rest.split_at(len);
+        self.rest = rest;
+
+        if let Ok(value) = word.parse::<i64>() {
+            return Ok(Expr::Literal(value));
+        }
+
+        let variable = match word {
+            "cycle" => Variable::Cycle,
+            "ip" => Variable::Ip,
+            _ => Variable::Register(Register::from_name(word).ok_or_else(|| self.error())?),
+        };
+
+        Ok(Expr::Variable(variable))
+    }
+}
+
+impl Expr {
+    /// Parse an expression
+    pub fn parse(source: &str) -> Result<Self, Day10Error> {
+        let mut parser = Parser {
+            source,
+            rest: source,
+        };
+
+        let expr = parser.binary(0)?;
+        if !parser.rest.trim().is_empty() {
+            return Err(parser.error());
+        }
+
+        Ok(expr)
+    }
+
+    /// Evaluate the expression on the current state of `cpu`
+    pub fn eval(&self, cpu: &Cpu) -> Result<i64, Day10Error> {
+        let value = match self {
+            Expr::Literal(value) => *value,
+            Expr::Variable(Variable::Cycle) => cpu.cycles_executed() as i64,
+            Expr::Variable(Variable::Ip) => cpu.ip() as i64,
+            Expr::Variable(Variable::Register(register)) => i64::from(cpu.register(*register)),
+            Expr::Neg(expr) => expr.eval(cpu)?.wrapping_neg(),
+            Expr::Not(expr) => i64::from(expr.eval(cpu)? == 0),
+            Expr::Binary(op, left, right) => {
+                let left = left.eval(cpu)?;
+
+                // Short circuit the logical operators
+                match op {
+                    BinaryOp::Or if left != 0 => return Ok(1),
+                    BinaryOp::And if left == 0 => return Ok(0),
+                    _ => {}
+                }
+
+                let right = right.eval(cpu)?;
+                match op {
+                    BinaryOp::Or | BinaryOp::And => i64::from(right != 0),
+                    BinaryOp::Eq => i64::from(left == right),
+                    BinaryOp::Ne => i64::from(left != right),
+                    BinaryOp::Lt => i64::from(left < right),
+                    BinaryOp::Le => i64::from(left <= right),
+                    BinaryOp::Gt => i64::from(left > right),
+                    BinaryOp::Ge => i64::from(left >= right),
+                    BinaryOp::Add => left.wrapping_add(right),
+                    BinaryOp::Sub => left.wrapping_sub(right),
+                    BinaryOp::Mul => left.wrapping_mul(right),
+                    BinaryOp::Div => left.checked_div(right).ok_or(Day10Error::DivideByZero)?,
+                    BinaryOp::Rem => left.checked_rem(right).ok_or(Day10Error::DivideByZero)?,
+                }
+            }
+        };
+
+        Ok(value)
+    }
+}
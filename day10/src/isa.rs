@@ -0,0 +1,377 @@
+//! The instruction set of the handheld device's CPU
+//!
+//! Every opcode is declared once in the [`opcodes!`] table below with its mnemonic,
+//! operand types, cycle cost and semantics.
+
+use crate::Day10Error;
+
+/// Registers available in the CPU
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Register {
+    X,
+    A,
+    B,
+    C,
+    D,
+}
+
+impl Register {
+    /// The number of registers
+    pub const COUNT: usize = 5;
+
+    /// Every register, in index order
+    pub const ALL: [Register; Register::COUNT] = [
+        Register::X,
+        Register::A,
+        Register::B,
+        Register::C,
+        Register::D,
+    ];
+
+    /// The name of the register in assembly
+    pub fn name(self) -> &'static str {
+        match self {
+            Register::X => "x",
+            Register::A => "a",
+            Register::B => "b",
+            Register::C => "c",
+            Register::D => "d",
+        }
+    }
+
+    /// Get the register with the given assembly `name`
+    pub fn from_name(name: &str) -> Option<Self> {
+        Register::ALL
+            .into_iter()
+            .find(|register| register.name() == name)
+    }
+}
+
+/// The register state of the CPU, indexed by [`Register`]
+pub type Registers = [i32; Register::COUNT];
+
+/// The number of words of memory
+pub const MEMORY_SIZE: usize = 16;
+
+/// The memory of the CPU, shared with the devices on the bus
+pub type Memory = [i32; MEMORY_SIZE];
+
+/// The kind of value an opcode expects for an operand
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OperandKind {
+    /// A register that is written
+    Register,
+
+    /// A register or an immediate value that is read
+    Value,
+
+    /// An instruction address to jump to
+    Target,
+
+    /// A memory address to load from or store to
+    Address,
+}
+
+/// A single operand of an instruction
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Operand {
+    /// A register
+    Register(Register),
+
+    /// An immediate value
+    Immediate(i32),
+
+    /// An instruction address
+    Target(usize),
+
+    /// A memory address
+    Address(usize),
+}
+
+impl Operand {
+    /// Returns `true` if this operand can be used where `kind` is expected
+    pub fn is(self, kind: OperandKind) -> bool {
+        matches!(
+            (kind, self),
+            (OperandKind::Register, Operand::Register(_))
+                | (
+                    OperandKind::Value,
+                    Operand::Register(_) | Operand::Immediate(_)
+                )
+                | (OperandKind::Target, Operand::Target(_))
+                | (OperandKind::Address, Operand::Address(_))
+        )
+    }
+
+    /// The value of a register or immediate operand
+    fn value(self, registers: &Registers) -> i32 {
+        match self {
+            Operand::Register(register) => registers[register as usize],
+            Operand::Immediate(value) => value,
+            Operand::Target(_) | Operand::Address(_) => {
+                unreachable!("Operand kinds checked by Instruction::new")
+            }
+        }
+    }
+
+    /// The register of a register operand
+    fn register(self) -> Register {
+        match self {
+            Operand::Register(register) => register,
+            _ => unreachable!("Operand kinds checked by Instruction::new"),
+        }
+    }
+
+    /// The address of a target operand
+    fn target(self) -> usize {
+        match self {
+            Operand::Target(target) => target,
+            _ => unreachable!("Operand kinds checked by Instruction::new"),
+        }
+    }
+
+    /// The memory address of an address operand
+    fn address(self) -> usize {
+        match self {
+            Operand::Address(address) => address,
+            _ => unreachable!("Operand kinds checked by Instruction::new"),
+        }
+    }
+}
+
+impl std::fmt::Display for Operand {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Operand::Register(register) => write!(f, "{}", register.name()),
+            Operand::Immediate(value) => write!(f, "{value}"),
+            Operand::Target(target) => write!(f, "@{target}"),
+            Operand::Address(address) => write!(f, "[{address}]"),
+        }
+    }
+}
+
+/// What the CPU does after executing an instruction
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Flow {
+    /// Continue with the next instruction
+    Next,
+
+    /// Continue at the given instruction address
+    Jump(usize),
+}
+
+/// The declaration of an opcode
+pub struct OpcodeInfo {
+    /// The mnemonic of the opcode in assembly
+    pub mnemonic: &'static str,
+
+    /// The kind of each operand
+    pub operands: &'static [OperandKind],
+
+    /// The number of cycles the opcode takes to execute
+    pub cycles: u32,
+
+    /// Apply the opcode with the given operands to the registers and memory
+    pub execute: fn(&mut Registers, &mut Memory, &[Operand]) -> Flow,
+}
+
+/// Declare the [`Opcode`] enum along with the [`OpcodeInfo`] of each opcode
+macro_rules! opcodes {
+    ($(
+        $(#[$doc:meta])*
+        $variant:ident $mnemonic:literal [$($kind:ident),*] $cycles:literal
+            => |$regs:ident, $mem:ident, $ops:ident| $body:expr;
+    )*) => {
+        /// Opcodes available for our CPU
+        #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+        pub enum Opcode {
+            $($(#[$doc])* $variant,)*
+        }
+
+        impl Opcode {
+            /// Every opcode, in declaration order
+            pub const ALL: &'static [Opcode] = &[$(Opcode::$variant,)*];
+
+            /// The declaration of this opcode
+            pub fn info(self) -> &'static OpcodeInfo {
+                match self {
+                    $(Opcode::$variant => {
+                        #[allow(unused_variables)]
+                        fn execute($regs: &mut Registers, $mem: &mut Memory, $ops: &[Operand]) -> Flow {
+                            $body
+                        }
+
+                        &OpcodeInfo {
+                            mnemonic: $mnemonic,
+                            operands: &[$(OperandKind::$kind),*],
+                            cycles: $cycles,
+                            execute,
+                        }
+                    })*
+                }
+            }
+        }
+    };
+}
+
+/// Jump to the target in `ops[1]` if `condition` holds for the value in `ops[0]`
+fn jump_if(regs: &Registers, ops: &[Operand], condition: fn(i32) -> bool) -> Flow {
+    if condition(ops[0].value(regs)) {
+        Flow::Jump(ops[1].target())
+    } else {
+        Flow::Next
+    }
+}
+
+opcodes! {
+    /// Do nothing
+    Noop "noop" [] 1 => |regs, mem, ops| Flow::Next;
+
+    /// Add a value to `x`
+    Addx "addx" [Value] 2 => |regs, mem, ops| {
+        regs[Register::X as usize] = regs[Register::X as usize].wrapping_add(ops[0].value(regs));
+        Flow::Next
+    };
+
+    /// Copy a value into a register
+    Mov "mov" [Register, Value] 1 => |regs, mem, ops| {
+        regs[ops[0].register() as usize] = ops[1].value(regs);
+        Flow::Next
+    };
+
+    /// Add a value to a register
+    Add "add" [Register, Value] 1 => |regs, mem, ops| {
+        let dst = ops[0].register() as usize;
+        regs[dst] = regs[dst].wrapping_add(ops[1].value(regs));
+        Flow::Next
+    };
+
+    /// Subtract a value from a register
+    Sub "sub" [Register, Value] 1 => |regs, mem, ops| {
+        let dst = ops[0].register() as usize;
+        regs[dst] = regs[dst].wrapping_sub(ops[1].value(regs));
+        Flow::Next
+    };
+
+    /// Multiply a register by a value
+    Mul "mul" [Register, Value] 3 => |regs, mem, ops| {
+        let dst = ops[0].register() as usize;
+        regs[dst] = regs[dst].wrapping_mul(ops[1].value(regs));
+        Flow::Next
+    };
+
+    /// Always jump to the target
+    Jmp "jmp" [Target] 1 => |regs, mem, ops| Flow::Jump(ops[0].target());
+
+    /// Jump to the target if the value is zero
+    Jz "jz" [Value, Target] 2 => |regs, mem, ops| jump_if(regs, ops, |value| value == 0);
+
+    /// Jump to the target if the value is not zero
+    Jnz "jnz" [Value, Target] 2 => |regs, mem, ops| jump_if(regs, ops, |value| value != 0);
+
+    /// Jump to the target if the value is greater than zero
+    Jgz "jgz" [Value, Target] 2 => |regs, mem, ops| jump_if(regs, ops, |value| value > 0);
+
+    /// Jump to the target if the value is less than zero
+    Jlz "jlz" [Value, Target] 2 => |regs, mem, ops| jump_if(regs, ops, |value| value < 0);
+
+    /// Copy a word of memory into a register
+    Load "load" [Register, Address] 1 => |regs, mem, ops| {
+        regs[ops[0].register() as usize] = mem[ops[1].address()];
+        Flow::Next
+    };
+
+    /// Copy a value into a word of memory
+    Store "store" [Value, Address] 1 => |regs, mem, ops| {
+        mem[ops[1].address()] = ops[0].value(regs);
+        Flow::Next
+    };
+}
+
+impl Opcode {
+    /// Get the opcode with the given assembly `mnemonic`
+    pub fn from_mnemonic(mnemonic: &str) -> Option<Self> {
+        Opcode::ALL
+            .iter()
+            .copied()
+            .find(|opcode| opcode.info().mnemonic == mnemonic)
+    }
+}
+
+/// The most operands taken by any opcode
+pub const MAX_OPERANDS: usize = 2;
+
+/// Instructions available for our CPU: an opcode and its operands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    /// The opcode to execute
+    opcode: Opcode,
+
+    /// The operands of the opcode. Only the first `opcode.info().operands.len()` are
+    /// used.
+    operands: [Operand; MAX_OPERANDS],
+}
+
+impl Instruction {
+    /// Create an instruction, checking the operands match the opcode's declaration and
+    /// the memory addresses are in range
+    pub fn new(opcode: Opcode, operands: &[Operand]) -> Result<Self, Day10Error> {
+        let kinds = opcode.info().operands;
+
+        if kinds.len() != operands.len()
+            || !kinds
+                .iter()
+                .zip(operands)
+                .all(|(kind, operand)| operand.is(*kind))
+        {
+            return Err(Day10Error::InvalidOperands(opcode.info().mnemonic));
+        }
+
+        for operand in operands {
+            if let Operand::Address(address) = operand {
+                if *address >= MEMORY_SIZE {
+                    return Err(Day10Error::InvalidAddress(*address));
+                }
+            }
+        }
+
+        let mut all_operands = [Operand::Immediate(0); MAX_OPERANDS];
+        all_operands[..operands.len()].copy_from_slice(operands);
+
+        Ok(Self {
+            opcode,
+            operands: all_operands,
+        })
+    }
+
+    /// The opcode of the instruction
+    pub fn opcode(&self) -> Opcode {
+        self.opcode
+    }
+
+    /// The operands of the instruction
+    pub fn operands(&self) -> &[Operand] {
+        &self.operands[..self.opcode.info().operands.len()]
+    }
+
+    /// The number of cycles the instruction takes to execute
+    pub fn cycles(&self) -> u32 {
+        self.opcode.info().cycles
+    }
+
+    /// Apply the instruction to the registers and memory, returning where to continue
+    pub fn execute(&self, registers: &mut Registers, memory: &mut Memory) -> Flow {
+        (self.opcode.info().execute)(registers, memory, self.operands())
+    }
+}
+
+impl std::fmt::Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.opcode.info().mnemonic)?;
+        for operand in self.operands() {
+            write!(f, " {operand}")?;
+        }
+
+        Ok(())
+    }
+}
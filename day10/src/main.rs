@@ -1,261 +1,368 @@
+mod asm;
+mod binary;
+mod cpu;
+mod crt;
+mod debug;
+mod expr;
+mod isa;
+mod ocr;
+mod render;
+
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use cpu::{Cpu, Execution};
+use crt::{Crt, Frame};
+use debug::{Debugger, Watch};
+use isa::{Instruction, Register};
+
 const INPUT: &str = include_str!("../input");
 
+/// The signal strength summed for part 1
+const SIGNAL_STRENGTH: &str = "cycle * x";
+
+/// The cycles where the signal strength is sampled for part 1: 20, 60, ..., 220
+const SIGNAL_CYCLES: &str = "cycle % 40 == 20 && cycle <= 220";
+
+/// The time each frame of the GIF is shown, in hundredths of a second
+const GIF_DELAY: u16 = 2;
+
 /// Errors possible during Day 10
-#[derive(Debug, Clone)]
-enum Day10Error {
-    /// An instruction was not a valid format: <OPCODE> [<VALUE>]
+#[derive(Debug)]
+pub enum Day10Error {
+    /// An instruction was not a valid format: <OPCODE> [<OPERAND>, ...]
     InvalidInstructionFormat(String),
 
-    /// A parsed instruction value did not fit in an `i32`
+    /// A parsed instruction operand was not a valid register, `i32` or address
     InvalidArgument(String),
 
     /// Parsed an unknown opcode
     UnknownOpcode(String),
 
+    /// The operands don't match the declaration of the opcode
+    InvalidOperands(&'static str),
+
+    /// A memory address is past the end of the memory
+    InvalidAddress(usize),
+
+    /// A jump target label is not defined
+    UnknownLabel(String),
+
+    /// A label is defined twice
+    DuplicateLabel(String),
+
     /// Attempted to execute without loading an instruction first
     InstructionNotLoaded,
-}
 
-/// The execution unit of the processor for Day 10
-struct Cpu {
-    /// The current instructions in the CPU
-    instructions: Vec<Instruction>,
+    /// Failed to read a program
+    Io(std::io::Error),
 
-    /// The register state of the CPU
-    registers: [i32; Register::Count as usize],
+    /// A file includes itself, directly or through other files
+    IncludeCycle(String),
 
-    /// The currently executing instruction if it takes longer than 1 clock cycle
-    pipeline: Option<Pipeline>,
+    /// A binary program is malformed
+    InvalidBinary(&'static str),
 
-    /// The instruction pointer
-    ip: usize,
+    /// Invalid command line argument
+    InvalidCommandLine(String),
 
-    /// Number of cycles executed
-    cycles_executed: usize,
-}
+    /// A debugger expression failed to parse
+    InvalidExpression(String),
 
-/// Is the CPU continuing to execute or is it finished
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-enum Execution {
-    Continue,
-    Finished,
-}
+    /// A debugger expression divided by zero
+    DivideByZero,
 
-impl Cpu {
-    pub fn from_input() -> Result<Self, Day10Error> {
-        let mut instructions = Vec::new();
+    /// The display to read (`width` by `height` pixels) isn't one letter high
+    InvalidDisplaySize(usize, usize),
 
-        // Parse each instruction line
-        for line in INPUT.split('\n') {
-            if line.is_empty() {
-                continue;
-            }
+    /// The display has letters that aren't in the font. Holds the text read with `?`
+    /// for each unknown letter, and the unknown letters.
+    UnknownGlyphs(String, Vec<ocr::UnknownGlyph>),
 
-            instructions.push(Instruction::try_from(line)?);
-        }
+    /// The CRT size (`width`, `height` and sprite width) has a zero
+    InvalidCrtSize(usize, usize, usize),
 
-        // Init the register state
-        let mut registers = [0; Register::Count as usize];
-        registers[Register::X as usize] = 1;
+    /// The CRT didn't complete a frame to render
+    NoFrame,
 
-        Ok(Cpu {
-            instructions,
-            registers,
-            pipeline: None,
-            ip: 0,
+    /// Failed to write a PNG
+    Image(aoc2022::image::ImageError),
 
-            // The problem starts cycles at 1. Use this value here to calculate the
-            // correct cycles when checking for an answer
-            cycles_executed: 1,
-        })
-    }
+    /// Failed to write a GIF
+    Gif(gif::EncodingError),
+
+    /// The scaled frame is too large for a GIF
+    ImageTooLarge,
+
+    /// An error at the given `file:line` of a program
+    At(String, Box<Day10Error>),
+}
 
-    /// Apply a given instruction to the current CPU state
-    pub fn execute(&mut self, instruction: Instruction) {
-        match instruction {
-            Instruction::Noop => {
-                // Nothing to do
+impl std::fmt::Display for Day10Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Day10Error::InvalidInstructionFormat(line) => {
+                write!(f, "invalid instruction: {line:?}")
+            }
+            Day10Error::InvalidArgument(line) => write!(f, "invalid operand: {line:?}"),
+            Day10Error::UnknownOpcode(opcode) => write!(f, "unknown opcode: {opcode:?}"),
+            Day10Error::InvalidOperands(mnemonic) => {
+                write!(f, "invalid operands for {mnemonic:?}")
+            }
+            Day10Error::InvalidAddress(address) => write!(
+                f,
+                "memory address {address} is out of range (0 to {})",
+                isa::MEMORY_SIZE - 1
+            ),
+            Day10Error::UnknownLabel(label) => write!(f, "unknown label: {label:?}"),
+            Day10Error::DuplicateLabel(label) => write!(f, "label defined twice: {label:?}"),
+            Day10Error::InstructionNotLoaded => write!(f, "no instruction loaded"),
+            Day10Error::Io(err) => write!(f, "{err}"),
+            Day10Error::IncludeCycle(path) => write!(f, "{path} includes itself"),
+            Day10Error::InvalidBinary(reason) => write!(f, "invalid binary program: {reason}"),
+            Day10Error::InvalidCommandLine(arg) => write!(f, "invalid argument: {arg:?}"),
+            Day10Error::InvalidExpression(expr) => write!(f, "invalid expression: {expr:?}"),
+            Day10Error::DivideByZero => write!(f, "division by zero"),
+            Day10Error::InvalidDisplaySize(width, height) => {
+                write!(f, "can't read letters from a {width}x{height} display")
             }
-            Instruction::Addx(val) => {
-                self.registers[Register::X as usize] += val;
+            Day10Error::UnknownGlyphs(text, glyphs) => {
+                write!(f, "unknown letters in {text:?}")?;
+                for glyph in glyphs {
+                    write!(f, "\n{glyph}")?;
+                }
+                Ok(())
             }
+            Day10Error::InvalidCrtSize(width, height, sprite_width) => write!(
+                f,
+                "invalid {width}x{height} CRT with a sprite {sprite_width} pixels wide"
+            ),
+            Day10Error::NoFrame => write!(f, "the CRT didn't complete a frame"),
+            Day10Error::Image(err) => write!(f, "{err}"),
+            Day10Error::Gif(err) => write!(f, "{err}"),
+            Day10Error::ImageTooLarge => write!(f, "the image is too large for a GIF"),
+            Day10Error::At(location, err) => write!(f, "{location}: {err}"),
         }
     }
+}
 
-    /// Step the CPU one clock cycle
-    pub fn step(&mut self) -> Result<Execution, Day10Error> {
-        // Grab the next instruction if there isn't one already executing
-        if self.pipeline.is_none() {
-            // If there are no more instructions to execute, the CPU is finished
-            if self.ip >= self.instructions.len() {
-                return Ok(Execution::Finished);
-            }
+/// Load the program in `path`, either encoded in the binary format or as assembly
+fn load_program(path: &Path) -> Result<Vec<Instruction>, Day10Error> {
+    let bytes = std::fs::read(path).map_err(Day10Error::Io)?;
 
-            // Fetch the next instruction
-            let instr = self.instructions[self.ip];
+    if bytes.starts_with(binary::MAGIC) {
+        binary::decode(&bytes)
+    } else {
+        asm::assemble_file(path)
+    }
+}
 
-            // Increment the instruction pointer
-            self.ip += 1;
+/// The command line options
+struct Options {
+    /// The program to run instead of the puzzle input
+    program: Option<PathBuf>,
 
-            // Set the instruction into the pipeline
-            self.pipeline = Some(Pipeline {
-                instruction: instr,
-                cycles_left: instr.cycles(),
-            });
-        }
+    /// Print the instructions with their addresses instead of running them
+    disassemble: bool,
 
-        // Each step call will always step the CPU cycles
-        self.cycles_executed += 1;
+    /// Write the program in the binary format to this file instead of running it
+    emit: Option<PathBuf>,
 
-        // Check if there is already an existing instruction executing in this pipeline
-        let Some(Pipeline { instruction, mut cycles_left }) = self.pipeline.take() else {
-            return Err(Day10Error::InstructionNotLoaded);
-        };
+    /// Run the program under the interactive debugger, reading commands from stdin
+    debug: bool,
 
-        // Reduce the number of cycles left for this instruction by 1
-        cycles_left -= 1;
-
-        // If this instruction has finished, set the instruction to execute as this
-        // one and reset the pipeline
-        if cycles_left == 0 {
-            self.execute(instruction);
-        } else {
-            // There are still cycles left to execute this instruction, so we can't
-            // execute it yet. Set the pipeline back and continue.
-            self.pipeline = Some(Pipeline {
-                instruction,
-                cycles_left,
-            });
-        }
+    /// Log the state at the start of every cycle to this file
+    trace: Option<PathBuf>,
 
-        // CPU still has instructions to execute, continue
-        Ok(Execution::Continue)
-    }
+    /// The width and height of the CRT in pixels
+    crt: (usize, usize),
 
-    /// Print the CPU state
-    pub fn _print(&self) {
-        println!("------- Cycle {:03} -------", self.cycles_executed);
-        println!(" X: {:4}", self.registers[Register::X as usize]);
-        println!("IP: {:4}", self.ip);
-        println!("--- Pipeline ---");
-        println!("{:?}", self.pipeline);
-        println!("--- Instrs ---");
-        for ip in self.ip..(self.ip + 5).min(self.instructions.len()) {
-            println!("{ip:4}: {:?}", self.instructions[ip]);
-        }
-        println!();
-    }
-}
+    /// The width of the CRT sprite in pixels
+    sprite_width: usize,
 
-/// A particular pipeline in the CPU that can execute opcodes for a given number of
-/// instructions
-#[derive(Debug, Copy, Clone)]
-struct Pipeline {
-    instruction: Instruction,
-    cycles_left: u32,
-}
+    /// Write the last complete CRT frame to this PNG file
+    png: Option<PathBuf>,
 
-/// Registers available in the CPU
-enum Register {
-    X,
+    /// Write the beam scanning every frame to this animated GIF file
+    gif: Option<PathBuf>,
 
-    // Used to count the number of enum variants
-    Count,
-}
+    /// The number of pixels drawn between two frames of the GIF
+    scan_interval: usize,
 
-/// Instructions available for our CPU
-#[derive(Debug, Clone, Copy)]
-enum Instruction {
-    Addx(i32),
-    Noop,
+    /// The side of the square drawn for each CRT pixel in the images
+    scale: usize,
 }
 
-impl Instruction {
-    pub fn cycles(self) -> u32 {
-        match self {
-            Instruction::Noop => 1,
-            Instruction::Addx(_) => 2,
+impl Options {
+    /// Parse the command line: `day10 [PROGRAM] [OPTIONS]`, see the README for the options
+    fn parse() -> Result<Self, Day10Error> {
+        let mut options = Options {
+            program: None,
+            disassemble: false,
+            emit: None,
+            debug: false,
+            trace: None,
+            crt: (Crt::WIDTH, Crt::HEIGHT),
+            sprite_width: Crt::SPRITE_WIDTH,
+            png: None,
+            gif: None,
+            scan_interval: 1,
+            scale: 8,
+        };
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| Day10Error::InvalidCommandLine(arg.clone()))
+            };
+            let number = |value: String| {
+                value
+                    .parse::<usize>()
+                    .map_err(|_| Day10Error::InvalidCommandLine(value))
+            };
+
+            match arg.as_str() {
+                "--disassemble" => options.disassemble = true,
+                "--emit" => options.emit = Some(PathBuf::from(value()?)),
+                "--debug" => options.debug = true,
+                "--trace" => options.trace = Some(PathBuf::from(value()?)),
+                "--crt" => {
+                    // Expected format: `40x6`
+                    let size = value()?;
+                    let Some((width, height)) = size.split_once('x') else {
+                        return Err(Day10Error::InvalidCommandLine(size));
+                    };
+                    options.crt = (number(width.to_string())?, number(height.to_string())?);
+                }
+                "--sprite" => options.sprite_width = number(value()?)?,
+                "--png" => options.png = Some(PathBuf::from(value()?)),
+                "--gif" => options.gif = Some(PathBuf::from(value()?)),
+                "--scan" => options.scan_interval = number(value()?)?,
+                "--scale" => options.scale = number(value()?)?,
+                _ if !arg.starts_with("--") && options.program.is_none() => {
+                    options.program = Some(PathBuf::from(arg));
+                }
+                _ => return Err(Day10Error::InvalidCommandLine(arg)),
+            }
         }
+
+        Ok(options)
     }
 }
 
-impl TryFrom<&str> for Instruction {
-    type Error = Day10Error;
+/// The last frame completed by the CRT
+fn last_frame(frames: &[Frame]) -> Result<&Frame, Day10Error> {
+    frames
+        .iter()
+        .rev()
+        .find(|frame| frame.is_complete())
+        .ok_or(Day10Error::NoFrame)
+}
 
-    fn try_from(line: &str) -> Result<Self, Self::Error> {
-        let mut iter = line.split(' ');
+/// Write the frames reported by the CRT to the PNG and GIF files of the `options`
+fn save_frames(options: &Options, frames: &[Frame]) -> Result<(), Day10Error> {
+    if let Some(path) = &options.png {
+        render::png(last_frame(frames)?, path, options.scale)?;
+    }
 
-        // Parse the opcode from the instruction
-        let opcode = iter
-            .next()
-            .ok_or_else(|| Day10Error::InvalidInstructionFormat(line.to_string()))?;
+    if let Some(path) = &options.gif {
+        render::gif(frames, path, options.scale, GIF_DELAY)?;
+    }
 
-        // Get the next argument for an instruction
-        macro_rules! arg {
-            () => {
-                iter.next()
-                    .ok_or_else(|| Day10Error::InvalidInstructionFormat(line.to_string()))?
-                    .parse::<i32>()
-                    .map_err(|_| Day10Error::InvalidArgument(line.to_string()))?
-            };
-        }
+    Ok(())
+}
 
-        match opcode {
-            "addx" => Ok(Instruction::Addx(arg!())),
-            "noop" => Ok(Instruction::Noop),
-            _ => Err(Day10Error::UnknownOpcode(opcode.to_string())),
-        }
+/// Run the debugged program to completion and print the final registers
+fn run_program(debugger: &mut Debugger) -> Result<(), Day10Error> {
+    while debugger.step()? == Execution::Continue {}
+    debugger.flush()?;
+
+    let cpu = debugger.cpu();
+    println!("Finished after {} cycles", cpu.cycles_executed() - 1);
+    for register in Register::ALL {
+        println!("{}: {}", register.name(), cpu.register(register));
     }
+
+    Ok(())
 }
 
+/// Usage: `day10 [PROGRAM] [--disassemble] [--emit FILE] [--debug] [--trace FILE]`
+///
+/// Without a program, solve the puzzle. Otherwise run the program in the `PROGRAM`
+/// file (assembly or binary) and print the final register state. With `--debug`, the
+/// program (or puzzle input) is run under the interactive debugger instead.
 fn main() -> Result<(), Day10Error> {
-    let mut cpu = Cpu::from_input()?;
-    let mut execution = Execution::Continue;
+    let options = Options::parse()?;
 
-    let mut sum = 0;
+    let instructions = match &options.program {
+        Some(path) => load_program(path)?,
+        None => asm::assemble(INPUT, "input", Path::new("."))?,
+    };
+    let mut cpu = Cpu::new(instructions);
 
-    let mut display = [['.'; 40]; 6];
-
-    while execution != Execution::Finished {
-        // Get the current cycles executed before stepping the CPU
-        let cycles = cpu.cycles_executed as i32;
+    if options.disassemble {
+        print!("{}", asm::disassemble(cpu.instructions()));
+        return Ok(());
+    }
 
-        // Only 240 cycles for this problem
-        if cycles >= 240 {
-            break;
-        }
+    if let Some(path) = &options.emit {
+        let bytes = binary::encode(cpu.instructions())?;
+        return std::fs::write(path, bytes).map_err(Day10Error::Io);
+    }
 
-        // Write the CRT pixel to the display if it is in bounds of the x position of the
-        // sprite
-        let x_pos = cpu.registers[Register::X as usize];
-        let col = ((cycles - 1) % 40) as usize;
-        let row = ((cycles - 1) / 40) as usize;
-        if [x_pos - 1, x_pos, x_pos + 1].contains(&(col as i32)) {
-            display[row][col] = '#';
-        } else {
-            display[row][col] = '.';
-        }
+    // Attach the CRT, keeping every frame it reports
+    let (width, height) = options.crt;
+    let mut crt = Crt::new(width, height, options.sprite_width)?;
+    let frames = Rc::new(RefCell::new(Vec::new()));
+    let sink = Rc::clone(&frames);
+    crt.set_on_frame(move |frame: &Frame| sink.borrow_mut().push(frame.clone()));
+    if options.gif.is_some() {
+        crt.set_scan_interval(options.scan_interval);
+    }
+    cpu.attach(crt);
 
-        // Step the CPU
-        execution = cpu.step()?;
+    let mut debugger = Debugger::new(cpu);
+    if let Some(path) = &options.trace {
+        let file = std::fs::File::create(path).map_err(Day10Error::Io)?;
+        debugger.set_trace(Box::new(std::io::BufWriter::new(file)));
+    }
 
-        // Get the new cycles executed
-        let cycles = cpu.cycles_executed as i32;
+    if options.debug {
+        debug::repl(&mut debugger, std::io::stdin().lock(), std::io::stdout())?;
+        return save_frames(&options, &frames.borrow());
+    }
 
-        // Calculate the signal strengths for part 1
-        if [20, 60, 100, 140, 180, 220].contains(&cycles) {
-            sum += cycles * cpu.registers[Register::X as usize];
-        }
+    if options.program.is_some() {
+        run_program(&mut debugger)?;
+        return save_frames(&options, &frames.borrow());
     }
 
+    // Sum the signal strengths for part 1 with a watch
+    let signal = debugger.add_watch(Watch::new(SIGNAL_STRENGTH, Some(SIGNAL_CYCLES))?);
+
+    // Step the CPU, sampling the watches and drawing the CRT every cycle
+    while debugger.step()? == Execution::Continue {}
+    debugger.flush()?;
+
+    let sum = debugger.watches()[signal].sum();
     println!("Part 1 signal strengths: {sum}");
 
-    for line in display {
-        for c in line {
-            print!("{c}");
+    let frames = frames.borrow();
+    let display = last_frame(&frames)?;
+    print!("{display}");
+
+    // Read the letters for part 2 after drawing them, so unknown letters can be seen
+    match ocr::recognize(&display.rows()) {
+        Ok(letters) => println!("Part 2 letters: {letters}"),
+        Err(err) => {
+            // Show the bitmaps of the unknown letters, which the `Debug` output of the
+            // error returned from `main` would escape
+            eprintln!("{err}");
+            return Err(err);
         }
-        println!();
     }
 
-    Ok(())
+    save_frames(&options, &frames)
 }
@@ -0,0 +1,160 @@
+//! Read the letters drawn on the CRT in the standard 4×6 Advent of Code font
+//!
+//! A few letters, like `Y`, are 5 pixels wide and fill the blank column between
+//! letters.
+
+use crate::Day10Error;
+
+/// The width of a letter in pixels
+pub const GLYPH_WIDTH: usize = 4;
+
+/// The height of a letter in pixels
+pub const GLYPH_HEIGHT: usize = 6;
+
+/// The horizontal distance between the start of two letters, leaving a blank column
+/// that only the wide letters use
+pub const GLYPH_STRIDE: usize = GLYPH_WIDTH + 1;
+
+/// Every known letter of the font. A glyph is matched against the letter and the blank
+/// column after it, so a 4 pixel wide glyph requires that column to be blank.
+const FONT: [(char, [&str; GLYPH_HEIGHT]); 18] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// A letter on the display that isn't in the font
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownGlyph {
+    /// The (0-indexed) position of the letter on the display
+    pub index: usize,
+
+    /// The pixels of the letter and the blank column after it, one `#`/`.` line per row
+    pub bitmap: String,
+}
+
+impl std::fmt::Display for UnknownGlyph {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "letter {}:", self.index)?;
+        write!(f, "{}", self.bitmap.trim_end())
+    }
+}
+
+/// Read the letters of a display given as rows of pixels, `true` when lit. A letter
+/// cell with no lit pixel reads as a space and trailing spaces are removed.
+pub fn recognize<R: AsRef<[bool]>>(rows: &[R]) -> Result<String, Day10Error> {
+    let width = rows.first().map_or(0, |row| row.as_ref().len());
+    if rows.len() != GLYPH_HEIGHT || rows.iter().any(|row| row.as_ref().len() != width) {
+        return Err(Day10Error::InvalidDisplaySize(width, rows.len()));
+    }
+
+    let mut text = String::new();
+    let mut unknown = Vec::new();
+
+    for (index, start) in (0..width).step_by(GLYPH_STRIDE).enumerate() {
+        // Include the blank column after the letter, which must stay blank unless the
+        // glyph is 5 pixels wide
+        let columns = start..(start + GLYPH_STRIDE).min(width);
+        let cell = rows
+            .iter()
+            .map(|row| &row.as_ref()[columns.clone()])
+            .collect::<Vec<_>>();
+
+        if cell.iter().all(|row| !row.contains(&true)) {
+            text.push(' ');
+            continue;
+        }
+
+        let letter = FONT.iter().find_map(|(letter, glyph)| {
+            let matches = cell.iter().zip(glyph).all(|(row, glyph)| {
+                row.iter()
+                    .zip(glyph.bytes().chain(std::iter::repeat(b'.')))
+                    .all(|(pixel, want)| *pixel == (want == b'#'))
+            });
+
+            matches.then_some(*letter)
+        });
+
+        match letter {
+            Some(letter) => text.push(letter),
+            None => {
+                text.push('?');
+                let bitmap = cell
+                    .iter()
+                    .map(|row| {
+                        let mut line = row
+                            .iter()
+                            .map(|pixel| if *pixel { '#' } else { '.' })
+                            .collect::<String>();
+                        line.push('\n');
+                        line
+                    })
+                    .collect();
+
+                unknown.push(UnknownGlyph { index, bitmap });
+            }
+        }
+    }
+
+    let text = text.trim_end().to_string();
+    if !unknown.is_empty() {
+        return Err(Day10Error::UnknownGlyphs(text, unknown));
+    }
+
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The rows of pixels of a display drawn with `#` for lit pixels
+    fn display(rows: [&str; GLYPH_HEIGHT]) -> Vec<Vec<bool>> {
+        rows.iter()
+            .map(|row| row.bytes().map(|pixel| pixel == b'#').collect())
+            .collect()
+    }
+
+    #[test]
+    fn recognize_wide_letter() {
+        let rows = display([
+            "#..#.#...#.##..",
+            "#..#.#...##..#.",
+            "####..#.#.#....",
+            "#..#...#..#.##.",
+            "#..#...#..#..#.",
+            "#..#...#...###.",
+        ]);
+        assert_eq!(recognize(&rows).unwrap(), "HYG");
+    }
+
+    #[test]
+    fn reject_lit_blank_column() {
+        // An `H` with a pixel lit in the blank column after it
+        let rows = display(["#..#.", "#..##", "####.", "#..#.", "#..#.", "#..#."]);
+        let Err(Day10Error::UnknownGlyphs(text, unknown)) = recognize(&rows) else {
+            panic!("the blank column after a narrow letter must stay blank");
+        };
+
+        assert_eq!(text, "?");
+        assert_eq!(
+            unknown[0].bitmap,
+            "#..#.\n#..##\n####.\n#..#.\n#..#.\n#..#.\n"
+        );
+    }
+}
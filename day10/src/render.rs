@@ -0,0 +1,93 @@
+//! Render the frames of the [`Crt`](crate::crt::Crt) as a PNG or an animated GIF
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use aoc2022::image::{write_rgb_png, Rgb};
+
+use crate::crt::Frame;
+use crate::Day10Error;
+
+/// The color of an unlit pixel
+const UNLIT: Rgb = [0x10, 0x10, 0x18];
+
+/// The color of a lit pixel
+const LIT: Rgb = [0x33, 0xff, 0x66];
+
+/// The color of the pixel the beam draws next
+const BEAM: Rgb = [0xff, 0x40, 0x40];
+
+/// The size of `frame` scaled up by `scale`, checked to fit a GIF
+fn gif_size(frame: &Frame, scale: usize) -> Result<(u16, u16), Day10Error> {
+    let size = |pixels: usize| {
+        pixels
+            .checked_mul(scale)
+            .and_then(|pixels| u16::try_from(pixels).ok())
+            .ok_or(Day10Error::ImageTooLarge)
+    };
+
+    Ok((size(frame.width())?, size(frame.height())?))
+}
+
+/// The color index of the pixel at column `x` of row `y` of `frame`: 0 for unlit, 1 for
+/// lit and 2 for the beam of an incomplete frame
+fn color_index(frame: &Frame, x: usize, y: usize) -> u8 {
+    if !frame.is_complete() && y * frame.width() + x == frame.beam() {
+        2
+    } else {
+        u8::from(frame.is_lit(x, y))
+    }
+}
+
+/// The color index of every image pixel of `frame` scaled up by `scale`
+fn indexes(frame: &Frame, scale: usize) -> Vec<u8> {
+    let mut data = Vec::with_capacity(frame.width() * frame.height() * scale * scale);
+
+    for y in 0..frame.height() {
+        let line = (0..frame.width())
+            .flat_map(|x| std::iter::repeat_n(color_index(frame, x, y), scale))
+            .collect::<Vec<_>>();
+
+        for _ in 0..scale {
+            data.extend_from_slice(&line);
+        }
+    }
+
+    data
+}
+
+/// Write `frame` to the PNG file at `path`, each pixel a `scale` sided square
+pub fn png(frame: &Frame, path: &Path, scale: usize) -> Result<(), Day10Error> {
+    let colors = [UNLIT, LIT, BEAM];
+    write_rgb_png(path, frame.width(), frame.height(), scale, |x, y| {
+        colors[usize::from(color_index(frame, x, y))]
+    })
+    .map_err(Day10Error::Image)
+}
+
+/// Write `frames` as a looping animated GIF at `path`, each pixel a `scale` sided
+/// square, showing each frame for `delay` hundredths of a second
+pub fn gif(frames: &[Frame], path: &Path, scale: usize, delay: u16) -> Result<(), Day10Error> {
+    let Some(first) = frames.first() else {
+        return Err(Day10Error::NoFrame);
+    };
+
+    let (width, height) = gif_size(first, scale)?;
+    let palette = [UNLIT, LIT, BEAM].concat();
+
+    let file = File::create(path).map_err(Day10Error::Io)?;
+    let mut encoder = gif::Encoder::new(BufWriter::new(file), width, height, &palette)
+        .map_err(Day10Error::Gif)?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(Day10Error::Gif)?;
+
+    for frame in frames {
+        let mut image = gif::Frame::from_indexed_pixels(width, height, indexes(frame, scale), None);
+        image.delay = delay;
+        encoder.write_frame(&image).map_err(Day10Error::Gif)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,186 @@
+//! Worry level expressions for the monkey operations
+//!
+//! ```text
+//! old * 19
+//! old + old
+//! (old * 2) + 1
+//! ```
+//!
+//! The only variable is `old`, the worry level before the operation. `*` and `/` bind
+//! tighter than `+` and `-`, and every operator is left associative.
+
+use crate::worry::Worry;
+use crate::Day11Error;
+
+/// A binary operator
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl BinaryOp {
+    /// The token of the operator
+    fn token(self) -> char {
+        match self {
+            BinaryOp::Add => '+',
+            BinaryOp::Sub => '-',
+            BinaryOp::Mul => '*',
+            BinaryOp::Div => '/',
+        }
+    }
+}
+
+/// A parsed expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    /// The worry level before the operation
+    Old,
+
+    /// An integer literal
+    Literal(u64),
+
+    /// A binary operation
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+/// Parses an expression by recursive descent
+struct Parser<'a> {
+    /// The full expression, for errors
+    source: &'a str,
+
+    /// The rest of the expression not parsed yet
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    /// The error for an invalid expression
+    fn error(&self) -> Day11Error {
+        Day11Error::InvalidExpression(self.source.to_string())
+    }
+
+    /// Skip whitespace and return `true` if the rest starts with `token`, consuming it
+    fn eat(&mut self, token: char) -> bool {
+        self.rest = self.rest.trim_start();
+        match self.rest.strip_prefix(token) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Parse a chain of the `ops`, with the operands parsed by `operand`
+    fn chain(
+        &mut self,
+        ops: [BinaryOp; 2],
+        operand: fn(&mut Self) -> Result<Expr, Day11Error>,
+    ) -> Result<Expr, Day11Error> {
+        let mut left = operand(self)?;
+
+        while let Some(op) = ops.into_iter().find(|op| self.eat(op.token())) {
+            let right = operand(self)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    /// Parse a sum: `term (('+' | '-') term)*`
+    fn sum(&mut self) -> Result<Expr, Day11Error> {
+        self.chain([BinaryOp::Add, BinaryOp::Sub], Self::term)
+    }
+
+    /// Parse a term: `factor (('*' | '/') factor)*`
+    fn term(&mut self) -> Result<Expr, Day11Error> {
+        self.chain([BinaryOp::Mul, BinaryOp::Div], Self::factor)
+    }
+
+    /// Parse a factor: `old`, an integer or a parenthesized sum
+    fn factor(&mut self) -> Result<Expr, Day11Error> {
+        if self.eat('(') {
+            let expr = self.sum()?;
+            if !self.eat(')') {
+                return Err(self.error());
+            }
+
+            return Ok(expr);
+        }
+
+        let len = self
+            .rest
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(self.rest.len());
+        let (word, rest) = self.rest.split_at(len);
+        self.rest = rest;
+
+        match word {
+            "old" => Ok(Expr::Old),
+            _ => word
+                .parse::<u64>()
+                .map(Expr::Literal)
+                .map_err(|_| self.error()),
+        }
+    }
+}
+
+impl Expr {
+    /// Parse an expression
+    pub fn parse(source: &str) -> Result<Self, Day11Error> {
+        let mut parser = Parser {
+            source,
+            rest: source,
+        };
+
+        let expr = parser.sum()?;
+        if !parser.rest.trim().is_empty() {
+            return Err(parser.error());
+        }
+
+        Ok(expr)
+    }
+
+    /// Returns `true` if the expression uses the operator `op`
+    pub fn contains(&self, op: BinaryOp) -> bool {
+        match self {
+            Expr::Old | Expr::Literal(_) => false,
+            Expr::Binary(binary, left, right) => {
+                *binary == op || left.contains(op) || right.contains(op)
+            }
+        }
+    }
+
+    /// Evaluate the expression for the worry level `old`. With a `modulus`, every
+    /// addition, subtraction and multiplication is done modulo `modulus`, so the result
+    /// is only right modulo `modulus` but never grows past it.
+    pub fn eval<W: Worry>(&self, old: &W, modulus: Option<u64>) -> Result<W, Day11Error> {
+        match self {
+            Expr::Old => Ok(old.clone()),
+            Expr::Literal(value) => Ok(W::from_u64(*value)),
+            Expr::Binary(op, left, right) => {
+                let left = left.eval(old, modulus)?;
+                let right = right.eval(old, modulus)?;
+                let result = match (op, modulus) {
+                    (BinaryOp::Div, _) | (_, None) => left.apply(*op, &right),
+                    (_, Some(modulus)) => Some(left.apply_mod(*op, &right, modulus)),
+                };
+
+                result
+                    .ok_or_else(|| Day11Error::OutOfRange(format!("{left} {} {right}", op.token())))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Expr::Old => write!(f, "old"),
+            Expr::Literal(value) => write!(f, "{value}"),
+            Expr::Binary(op, left, right) => write!(f, "({left} {} {right})", op.token()),
+        }
+    }
+}
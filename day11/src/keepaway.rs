@@ -0,0 +1,194 @@
+//! Simulate the monkeys playing keep away
+
+use std::collections::VecDeque;
+
+use num_bigint::BigInt;
+
+use crate::expr::{BinaryOp, Expr};
+use crate::monkey::Monkey;
+use crate::worry::Worry;
+use crate::Day11Error;
+
+/// How worry levels are kept in check after each inspection
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Relief {
+    /// Divide the worry level by the given value, rounding down (part 1)
+    Divide(u64),
+
+    /// Keep the worry level modulo the least common multiple of every divisibility
+    /// test (part 2), after every operation as well as after the inspection. Adding,
+    /// subtracting and multiplying modulo the LCM keep every test result, but dividing
+    /// doesn't, so operations with `/` are rejected.
+    Lcm,
+
+    /// Keep the exact worry levels as arbitrary precision integers
+    None,
+}
+
+impl std::str::FromStr for Relief {
+    type Err = Day11Error;
+
+    /// Parse `divide:N`, `lcm` or `none`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some(("divide", divisor)) => divisor
+                .parse::<u64>()
+                .map(Relief::Divide)
+                .map_err(|_| Day11Error::InvalidCommandLine(s.to_string())),
+            None if s == "lcm" => Ok(Relief::Lcm),
+            None if s == "none" => Ok(Relief::None),
+            _ => Err(Day11Error::InvalidCommandLine(s.to_string())),
+        }
+    }
+}
+
+/// The result of a keep away simulation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Simulation {
+    /// The number of items each monkey inspected
+    pub inspections: Vec<u64>,
+
+    /// The product of the two largest inspection counts, which doesn't fit a `u64`
+    /// for extrapolated counts
+    pub monkey_business: u128,
+}
+
+impl Simulation {
+    /// The result for the number of items each monkey inspected
+    pub fn new(inspections: Vec<u64>) -> Self {
+        let mut best = inspections.clone();
+        best.sort_unstable_by(|a, b| b.cmp(a));
+        let monkey_business = match best[..] {
+            [first, second, ..] => u128::from(first) * u128::from(second),
+            _ => 0,
+        };
+
+        Self {
+            inspections,
+            monkey_business,
+        }
+    }
+}
+
+/// The greatest common divisor of `a` and `b`
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    a
+}
+
+/// The least common multiple of the divisibility tests of every monkey
+fn lcm(monkeys: &[Monkey]) -> Result<u64, Day11Error> {
+    monkeys.iter().try_fold(1, |lcm: u64, monkey| {
+        (lcm / gcd(lcm, monkey.test_val))
+            .checked_mul(monkey.test_val)
+            .ok_or_else(|| Day11Error::OutOfRange(format!("lcm({lcm}, {})", monkey.test_val)))
+    })
+}
+
+/// Simulate `rounds` rounds of the `monkeys` with the `relief` applied after each
+/// inspection. Worry levels are `i128`, except without relief.
+pub fn simulate(
+    monkeys: &[Monkey],
+    rounds: usize,
+    relief: Relief,
+) -> Result<Simulation, Day11Error> {
+    match relief {
+        Relief::None => simulate_with::<BigInt>(monkeys, rounds, relief),
+        _ => simulate_with::<i128>(monkeys, rounds, relief),
+    }
+}
+
+/// Simulate like [`simulate`] but with exact big integer worry levels for every
+/// relief, to check the `i128` results on a small number of rounds
+pub fn simulate_exact(
+    monkeys: &[Monkey],
+    rounds: usize,
+    relief: Relief,
+) -> Result<Simulation, Day11Error> {
+    simulate_with::<BigInt>(monkeys, rounds, relief)
+}
+
+/// The function giving the new worry level of an item from a monkey's operation,
+/// with `relief` applied to the `monkeys`' worry levels after the inspection
+pub fn inspect<W: Worry>(
+    monkeys: &[Monkey],
+    relief: Relief,
+) -> Result<impl Fn(&Expr, &W) -> Result<W, Day11Error>, Day11Error> {
+    // Without relief, dividing by 1 leaves the worry level unchanged
+    let (divisor, modulus) = match relief {
+        Relief::Divide(0) => return Err(Day11Error::InvalidRelief),
+        Relief::Divide(divisor) => (divisor, None),
+        Relief::Lcm => {
+            // Division doesn't keep remainders: with an LCM of 3, 4 / 2 and 1 / 2
+            // differ modulo 3 even though 4 and 1 don't
+            let dividing = monkeys
+                .iter()
+                .position(|monkey| monkey.operation.contains(BinaryOp::Div));
+            if let Some(monkey) = dividing {
+                return Err(Day11Error::LcmDivision(monkey));
+            }
+
+            (1, Some(lcm(monkeys)?))
+        }
+        Relief::None => (1, None),
+    };
+
+    Ok(move |operation: &Expr, worry: &W| {
+        let worry = operation.eval(worry, modulus)?;
+        Ok(match modulus {
+            Some(modulus) => worry.rem_u64(modulus),
+            None if divisor > 1 => worry.div_u64(divisor),
+            None => worry,
+        })
+    })
+}
+
+/// Simulate with worry levels of type `W`
+fn simulate_with<W: Worry>(
+    monkeys: &[Monkey],
+    rounds: usize,
+    relief: Relief,
+) -> Result<Simulation, Day11Error> {
+    let inspections = play::<W>(monkeys, rounds, inspect(monkeys, relief)?)?;
+    Ok(Simulation::new(inspections))
+}
+
+/// Play `rounds` rounds with worry levels of type `W`, giving the new worry level of
+/// each inspected item with `inspect`, and return the number of items each monkey
+/// inspected
+fn play<W: Worry>(
+    monkeys: &[Monkey],
+    rounds: usize,
+    inspect: impl Fn(&Expr, &W) -> Result<W, Day11Error>,
+) -> Result<Vec<u64>, Day11Error> {
+    let mut items = monkeys
+        .iter()
+        .map(|monkey| monkey.items.iter().map(|item| W::from_u64(*item)).collect())
+        .collect::<Vec<VecDeque<W>>>();
+    let mut inspections = vec![0; monkeys.len()];
+
+    for _ in 0..rounds {
+        for (index, monkey) in monkeys.iter().enumerate() {
+            while let Some(item) = items[index].pop_front() {
+                inspections[index] += 1;
+
+                // Calculate the new value of the item based on the operation for this
+                // monkey, then feel relieved that it wasn't damaged
+                let val = inspect(&monkey.operation, &item)?;
+
+                // Based on the result of the new value, move the item to another monkey
+                let target = if val.divisible_by(monkey.test_val) {
+                    monkey.true_monkey
+                } else {
+                    monkey.false_monkey
+                };
+                items[target].push_back(val);
+            }
+        }
+    }
+
+    Ok(inspections)
+}
@@ -1,208 +1,299 @@
-use std::collections::VecDeque;
+mod expr;
+mod keepaway;
+mod monkey;
+mod route;
+mod worry;
+
+use std::path::PathBuf;
+
+use keepaway::{simulate, simulate_exact, Relief, Simulation};
+use monkey::Monkey;
+use route::Route;
 
 const INPUT: &str = include_str!("../input");
 
+/// The number of rounds of part 1
+const PART1_ROUNDS: usize = 20;
+
+/// The worry levels are divided by this after each inspection in part 1
+const PART1_RELIEF: u64 = 3;
+
+/// The number of rounds of part 2
+const PART2_ROUNDS: usize = 10000;
+
+/// Errors possible during Day 11
 #[derive(Debug)]
-enum Day11Error {
+pub enum Day11Error {
+    /// Failed to read the input file
+    Io(std::io::Error),
+
+    /// A number failed to parse
     Num(std::num::ParseIntError),
-    UnknownOperation(&'static str),
-}
 
-/// The operation to perform
-#[derive(Debug, Copy, Clone)]
-enum Operation {
-    Add,
-    Mul,
-    Square,
-}
+    /// A line of a monkey isn't the expected line. Holds the expected start of the
+    /// line and the line found, if any.
+    InvalidLine(&'static str, Option<String>),
 
-#[derive(Debug, Clone)]
-struct Monkey {
-    /// Items currently held by the monkey
-    items: VecDeque<u64>,
+    /// An operation isn't a valid expression
+    InvalidExpression(String),
 
-    /// Number of items this monkey has inspected
-    inspected: u64,
+    /// An operation gave a worry level out of the `i128` range
+    OutOfRange(String),
 
-    /// Operation used to change the items being moved
-    operation: Operation,
+    /// A monkey's ID isn't its position in the input. Holds the position and the ID.
+    MonkeyOutOfOrder(usize, usize),
 
-    /// The right hand side of the operation
-    operation_val: u64,
+    /// A monkey tests for divisibility by zero. Holds the monkey.
+    DivisibleByZero(usize),
 
-    /// `divisible by` value
-    test_val: u64,
+    /// A monkey throws to a monkey that doesn't exist. Holds the monkey and the target.
+    UnknownMonkey(usize, usize),
 
-    /// Monkey ID to throw the item true on `true` result of `item % test_val == 0`
-    true_monkey: usize,
+    /// A monkey throws to itself, which would never finish its turn
+    ThrowToSelf(usize),
 
-    /// Monkey ID to throw the item false on `false` result of `item % test_val == 0`
-    false_monkey: usize,
-}
+    /// The input has no monkey
+    NoMonkeys,
+
+    /// The relief divides the worry levels by zero
+    InvalidRelief,
 
-impl Monkey {
-    pub fn from_str(data: &'static str) -> Result<Monkey, Day11Error> {
-        let mut iter = data.lines();
+    /// The `lcm` relief is used with a monkey whose operation divides. Holds the
+    /// monkey.
+    LcmDivision(usize),
 
-        // Ignore the monkey ID
-        let _monkey_id = iter.next();
+    /// A route was traced for too few rounds to extrapolate from. Holds the monkey
+    /// holding the item at the start and its starting worry level.
+    RouteTooShort(usize, u64),
 
-        // Parse line 2: Monkey items
-        // Expected input:
-        // Starting items: 60, 84, 84, 65
-        let items_iter = iter.next().unwrap().split(':').nth(1).unwrap();
-        let mut items = VecDeque::new();
+    /// A simulation doesn't match the exact worry levels
+    Mismatch(String),
 
-        for item in items_iter.split(' ').filter(|x| !x.is_empty()) {
-            println!("{item}");
-            let item = item
-                .replace(',', " ")
-                .parse::<u64>()
-                .map_err(Day11Error::Num)?;
+    /// Invalid command line argument
+    InvalidCommandLine(String),
+}
 
-            items.push_back(item);
+impl std::fmt::Display for Day11Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Day11Error::Io(err) => write!(f, "{err}"),
+            Day11Error::Num(err) => write!(f, "{err}"),
+            Day11Error::InvalidLine(expected, Some(line)) => {
+                write!(f, "expected {expected:?}, found {line:?}")
+            }
+            Day11Error::InvalidLine(expected, None) => write!(f, "missing {expected:?}"),
+            Day11Error::InvalidExpression(expr) => write!(f, "invalid operation: {expr:?}"),
+            Day11Error::OutOfRange(operation) => {
+                write!(f, "worry level out of range: {operation}")
+            }
+            Day11Error::MonkeyOutOfOrder(index, id) => {
+                write!(f, "monkey {index} of the input is numbered {id}")
+            }
+            Day11Error::DivisibleByZero(monkey) => {
+                write!(f, "monkey {monkey} tests divisibility by zero")
+            }
+            Day11Error::UnknownMonkey(monkey, target) => {
+                write!(f, "monkey {monkey} throws to unknown monkey {target}")
+            }
+            Day11Error::ThrowToSelf(monkey) => write!(f, "monkey {monkey} throws to itself"),
+            Day11Error::NoMonkeys => write!(f, "no monkey found"),
+            Day11Error::InvalidRelief => write!(f, "relief divides by zero"),
+            Day11Error::LcmDivision(monkey) => write!(
+                f,
+                "monkey {monkey} divides in its operation, which the lcm relief can't \
+                 keep exact"
+            ),
+            Day11Error::RouteTooShort(monkey, worry) => write!(
+                f,
+                "the route of item {worry} of monkey {monkey} has no cycle to extrapolate"
+            ),
+            Day11Error::Mismatch(reason) => write!(f, "mismatch: {reason}"),
+            Day11Error::InvalidCommandLine(arg) => write!(f, "invalid argument: {arg:?}"),
         }
+    }
+}
+
+/// The command line options
+struct Options {
+    /// The monkeys file to read instead of the puzzle input
+    input: Option<PathBuf>,
 
-        let op_line = iter.next().unwrap();
+    /// The number of rounds to simulate instead of solving the puzzle
+    rounds: Option<usize>,
 
-        let mut op_iter = op_line.split(" new = old ").nth(1).unwrap().split(' ');
+    /// The relief to simulate with instead of solving the puzzle
+    relief: Option<Relief>,
 
-        let mut op = match op_iter.next() {
-            Some("+") => Operation::Add,
-            Some("*") => Operation::Mul,
-            _ => return Err(Day11Error::UnknownOperation(op_line)),
+    /// Simulate with exact big integer worry levels
+    exact: bool,
+
+    /// Check the `i128` simulations against exact worry levels
+    verify: bool,
+
+    /// Print the route of every item
+    trace: bool,
+
+    /// Extrapolate the inspections for this number of rounds from the item routes
+    extrapolate: Option<usize>,
+}
+
+impl Options {
+    /// Parse the command line: `day11 [--input FILE] [--rounds N]
+    /// [--relief divide:N|lcm|none] [--exact] [--verify] [--trace] [--extrapolate N]`
+    fn parse() -> Result<Self, Day11Error> {
+        let mut options = Options {
+            input: None,
+            rounds: None,
+            relief: None,
+            exact: false,
+            verify: false,
+            trace: false,
+            extrapolate: None,
         };
 
-        let op_val = match op_iter.next() {
-            Some("old") => {
-                op = Operation::Square;
-                0
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| Day11Error::InvalidCommandLine(arg.clone()))
+            };
+            let number = |value: String| {
+                value
+                    .parse::<usize>()
+                    .map_err(|_| Day11Error::InvalidCommandLine(value))
+            };
+
+            match arg.as_str() {
+                "--input" => options.input = Some(PathBuf::from(value()?)),
+                "--rounds" => options.rounds = Some(number(value()?)?),
+                "--relief" => options.relief = Some(value()?.parse()?),
+                "--exact" => options.exact = true,
+                "--verify" => options.verify = true,
+                "--trace" => options.trace = true,
+                "--extrapolate" => options.extrapolate = Some(number(value()?)?),
+                _ => return Err(Day11Error::InvalidCommandLine(arg)),
             }
-            Some(val) => val.parse::<u64>().map_err(Day11Error::Num)?,
-            _ => return Err(Day11Error::UnknownOperation(op_line)),
+        }
+
+        Ok(options)
+    }
+}
+
+/// Check the `i128` simulations of both parts' reliefs for `rounds` rounds against
+/// exact worry levels. Keeping the worry levels modulo the LCM must give the same
+/// inspections as not relieving them at all.
+fn verify(monkeys: &[Monkey], rounds: usize) -> Result<(), Day11Error> {
+    let part1 = Relief::Divide(PART1_RELIEF);
+    let checks = [
+        (
+            part1,
+            simulate(monkeys, rounds, part1)?,
+            simulate_exact(monkeys, rounds, part1)?,
+        ),
+        (
+            Relief::Lcm,
+            simulate(monkeys, rounds, Relief::Lcm)?,
+            simulate_exact(monkeys, rounds, Relief::None)?,
+        ),
+    ];
+
+    for (relief, result, exact) in checks {
+        if result != exact {
+            return Err(Day11Error::Mismatch(format!(
+                "{relief:?} after {rounds} rounds: {:?} but exactly {:?}",
+                result.inspections, exact.inspections
+            )));
+        }
+
+        println!(
+            "{relief:?}: {:?} matches the exact worry levels",
+            result.inspections
+        );
+    }
+
+    Ok(())
+}
+
+/// Print the route of every item
+fn print_routes(routes: &[Route]) {
+    for route in routes {
+        let cycle = match (route.cycle_start, route.cycle_len()) {
+            (Some(start), Some(len)) => format!("{start} rounds then a cycle of {len} rounds"),
+            _ => format!("no cycle in {} rounds", route.rounds.len()),
         };
 
-        let test_val = iter
-            .next()
-            .unwrap()
-            .split("divisible by ")
-            .nth(1)
-            .unwrap()
-            .parse::<u64>()
-            .map_err(Day11Error::Num)?;
-
-        let true_monkey = iter
-            .next()
-            .unwrap()
-            .split("If true: throw to monkey ")
-            .nth(1)
-            .unwrap()
-            .parse::<usize>()
-            .map_err(Day11Error::Num)?;
-
-        let false_monkey = iter
-            .next()
-            .unwrap()
-            .split("If false: throw to monkey ")
-            .nth(1)
-            .unwrap()
-            .parse::<usize>()
-            .map_err(Day11Error::Num)?;
-
-        Ok(Monkey {
-            items,
-            inspected: 0,
-            operation: op,
-            operation_val: op_val,
-            test_val,
-            true_monkey,
-            false_monkey,
-        })
+        println!("Monkey {} item {}: {cycle}", route.monkey, route.worry);
+        println!("  {route}");
     }
 }
 
+/// Print the number of items each monkey inspected and the monkey business
+fn print_simulation(result: &Simulation) {
+    for (index, inspected) in result.inspections.iter().enumerate() {
+        println!("Monkey {index}: {inspected} inspections");
+    }
+    println!("Monkey business: {}", result.monkey_business);
+}
+
+/// Usage: `day11 [--input FILE] [--rounds N] [--relief divide:N|lcm|none] [--exact]
+/// [--verify] [--trace] [--extrapolate N]`
+///
+/// The monkeys are read from `FILE` instead of the puzzle input if given. Without
+/// other options, solve both parts. Otherwise simulate the given number of rounds
+/// (default 20) with the given relief (default `divide:3`) and print the number of
+/// items each monkey inspected, with exact worry levels if `--exact` is given.
+///
+/// `--verify` checks the rounds (default 20) of both parts against exact worry levels.
+/// `--trace` and `--extrapolate` trace the items for the rounds (default 10000) with
+/// the relief (default `lcm`), printing the routes or the inspections for the
+/// extrapolated number of rounds.
 fn main() -> Result<(), Day11Error> {
-    let mut orig_monkeys = Vec::new();
+    let options = Options::parse()?;
+    let input = match &options.input {
+        Some(path) => std::fs::read_to_string(path).map_err(Day11Error::Io)?,
+        None => INPUT.to_string(),
+    };
+    let monkeys = monkey::parse_monkeys(&input)?;
+
+    if options.verify {
+        return verify(&monkeys, options.rounds.unwrap_or(PART1_ROUNDS));
+    }
+
+    if options.trace || options.extrapolate.is_some() {
+        let rounds = options.rounds.unwrap_or(PART2_ROUNDS);
+        let relief = options.relief.unwrap_or(Relief::Lcm);
+        let routes = route::trace(&monkeys, rounds, relief)?;
 
-    fn operation(op: Operation, old: u64, val: u64) -> u64 {
-        match op {
-            Operation::Add => old + val,
-            Operation::Mul => old * val,
-            Operation::Square => old * old,
+        if options.trace {
+            print_routes(&routes);
+        }
+
+        if let Some(rounds) = options.extrapolate {
+            let inspections = route::extrapolate(&routes, monkeys.len(), rounds)?;
+            print_simulation(&Simulation::new(inspections));
         }
-    }
 
-    // Expected input:
-    // Monkey 0:
-    //   Starting items: 64
-    //   Operation: new = old * 7
-    //   Test: divisible by 13
-    //     If true: throw to monkey 1
-    //     If false: throw to monkey 3
-    for section in INPUT.split("\n\n") {
-        orig_monkeys.push(Monkey::from_str(section)?);
+        return Ok(());
     }
 
-    for part in [Some(3), None] {
-        // Reset the monkeys for each part
-        let mut monkeys = orig_monkeys.clone();
-
-        // Get the least common multiple for all of the test values
-        let div_by: u64 = monkeys.iter().map(|m| m.test_val).product();
-
-        for round in 1..10001 {
-            for index in 0..monkeys.len() {
-                loop {
-                    let Some(item) = monkeys[index].items.pop_front() else { break };
-                    let monkey = &mut monkeys[index];
-
-                    // Increment the number of items this monkey has inspected
-                    monkey.inspected += 1;
-
-                    // Calculate the new value of the item based on the operation and value for this Monkey
-                    let mut val = operation(monkey.operation, item, monkey.operation_val);
-
-                    // Part 1 had a divide by 3 rule for the items
-                    if let Some(div_val) = part {
-                        val /= div_val;
-                    } else {
-                        val %= div_by;
-                    }
-
-                    // Based on the result of the new value, move the item to another monkey
-                    if val % monkey.test_val == 0 {
-                        let true_monkey = monkey.true_monkey;
-                        monkeys[true_monkey].items.push_back(val);
-                    } else {
-                        let false_monkey = monkey.false_monkey;
-                        monkeys[false_monkey].items.push_back(val);
-                    }
-                }
-            }
+    if options.rounds.is_some() || options.relief.is_some() || options.exact {
+        let rounds = options.rounds.unwrap_or(PART1_ROUNDS);
+        let relief = options.relief.unwrap_or(Relief::Divide(PART1_RELIEF));
+        let result = if options.exact {
+            simulate_exact(&monkeys, rounds, relief)?
+        } else {
+            simulate(&monkeys, rounds, relief)?
+        };
 
-            if [1, 20, 1000, 2000, 10000].contains(&round) {
-                let mut best = monkeys.iter().map(|m| m.inspected).collect::<Vec<_>>();
-
-                let check = match round {
-                    1 => [2, 4, 3, 6],
-                    20 => [99, 97, 8, 103],
-                    1000 => [5204, 4792, 199, 5192],
-                    _ => [0, 0, 0, 0],
-                };
-                if best == check {
-                    println!("{round} | Div by: {div_by}")
-                }
-
-                println!("{best:?}");
-
-                best.sort();
-                best.reverse();
-                if let Some([one, two]) = best.chunks(2).next() {
-                    println!("{one} {two} {}", one * two);
-                }
-            }
-        }
+        print_simulation(&result);
+        return Ok(());
     }
 
+    let part1 = simulate(&monkeys, PART1_ROUNDS, Relief::Divide(PART1_RELIEF))?;
+    println!("Part 1 monkey business: {}", part1.monkey_business);
+
+    let part2 = simulate(&monkeys, PART2_ROUNDS, Relief::Lcm)?;
+    println!("Part 2 monkey business: {}", part2.monkey_business);
+
     Ok(())
 }
@@ -0,0 +1,126 @@
+//! Parse the monkeys of the puzzle input
+
+use crate::expr::Expr;
+use crate::Day11Error;
+
+/// A monkey playing keep away
+#[derive(Debug, Clone)]
+pub struct Monkey {
+    /// The worry level of every item held at the start
+    pub items: Vec<u64>,
+
+    /// Operation giving the new worry level of an item from the `old` one
+    pub operation: Expr,
+
+    /// `divisible by` value
+    pub test_val: u64,
+
+    /// Monkey ID to throw the item true on `true` result of `item % test_val == 0`
+    pub true_monkey: usize,
+
+    /// Monkey ID to throw the item false on `false` result of `item % test_val == 0`
+    pub false_monkey: usize,
+}
+
+/// Get the rest of `line` after `prefix`, ignoring the indentation
+fn field<'a>(line: Option<&'a str>, prefix: &'static str) -> Result<&'a str, Day11Error> {
+    let line = line.ok_or(Day11Error::InvalidLine(prefix, None))?;
+    line.trim()
+        .strip_prefix(prefix)
+        .ok_or_else(|| Day11Error::InvalidLine(prefix, Some(line.to_string())))
+}
+
+impl Monkey {
+    /// Parse the six lines of the monkey at `index` in the input
+    pub fn from_str(data: &str, index: usize) -> Result<Monkey, Day11Error> {
+        let mut iter = data.lines();
+
+        // Throws target monkeys by their position, so the ID must match it
+        // Expected input:
+        // Monkey 0:
+        let line = iter.next();
+        let id = field(line, "Monkey ")?
+            .strip_suffix(':')
+            .ok_or_else(|| Day11Error::InvalidLine("Monkey N:", line.map(str::to_string)))?
+            .parse::<usize>()
+            .map_err(Day11Error::Num)?;
+        if id != index {
+            return Err(Day11Error::MonkeyOutOfOrder(index, id));
+        }
+
+        // Parse line 2: Monkey items
+        // Expected input:
+        // Starting items: 60, 84, 84, 65
+        let items_iter = field(iter.next(), "Starting items:")?;
+        let items = items_iter
+            .split(',')
+            .map(str::trim)
+            .filter(|x| !x.is_empty())
+            .map(|item| item.parse::<u64>().map_err(Day11Error::Num))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Expected input:
+        // Operation: new = (old * 2) + 1
+        let operation = Expr::parse(field(iter.next(), "Operation: new =")?)?;
+
+        let test_val = field(iter.next(), "Test: divisible by ")?
+            .parse::<u64>()
+            .map_err(Day11Error::Num)?;
+
+        let true_monkey = field(iter.next(), "If true: throw to monkey ")?
+            .parse::<usize>()
+            .map_err(Day11Error::Num)?;
+
+        let false_monkey = field(iter.next(), "If false: throw to monkey ")?
+            .parse::<usize>()
+            .map_err(Day11Error::Num)?;
+
+        Ok(Monkey {
+            items,
+            operation,
+            test_val,
+            true_monkey,
+            false_monkey,
+        })
+    }
+}
+
+/// Parse every monkey of the `input`, checking that the tests and throws can be
+/// executed
+pub fn parse_monkeys(input: &str) -> Result<Vec<Monkey>, Day11Error> {
+    // Expected input:
+    // Monkey 0:
+    //   Starting items: 64
+    //   Operation: new = old * 7
+    //   Test: divisible by 13
+    //     If true: throw to monkey 1
+    //     If false: throw to monkey 3
+    let monkeys = input
+        .split("\n\n")
+        .filter(|section| !section.trim().is_empty())
+        .enumerate()
+        .map(|(index, section)| Monkey::from_str(section, index))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if monkeys.is_empty() {
+        return Err(Day11Error::NoMonkeys);
+    }
+
+    for (index, monkey) in monkeys.iter().enumerate() {
+        if monkey.test_val == 0 {
+            return Err(Day11Error::DivisibleByZero(index));
+        }
+
+        for target in [monkey.true_monkey, monkey.false_monkey] {
+            if target >= monkeys.len() {
+                return Err(Day11Error::UnknownMonkey(index, target));
+            }
+
+            if target == index {
+                return Err(Day11Error::ThrowToSelf(index));
+            }
+        }
+    }
+
+    Ok(monkeys)
+}
@@ -0,0 +1,190 @@
+//! Trace the route of every item through the monkeys
+//!
+//! An item's route doesn't depend on the other items: during a round, an item thrown
+//! to a later monkey is inspected again in the same round, and one thrown to an
+//! earlier monkey waits for the next round. Once the worry levels are bounded (like
+//! with the [`Relief::Lcm`] relief), an item eventually comes back to a monkey with a
+//! worry level it already had at the start of a round and repeats the same rounds
+//! forever. The cycle gives the inspections for any number of rounds.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use num_bigint::BigInt;
+
+use crate::keepaway::{inspect, Relief};
+use crate::monkey::Monkey;
+use crate::worry::Worry;
+use crate::Day11Error;
+
+/// The route of one item through the monkeys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    /// The monkey holding the item at the start
+    pub monkey: usize,
+
+    /// The worry level of the item at the start
+    pub worry: u64,
+
+    /// The monkeys inspecting the item during each traced round, in order
+    pub rounds: Vec<Vec<usize>>,
+
+    /// The (0-indexed) round where the route starts repeating the rounds after it, if
+    /// the item came back to a state it had at the start of this round
+    pub cycle_start: Option<usize>,
+}
+
+impl Route {
+    /// The number of rounds repeated forever, if a cycle was found
+    pub fn cycle_len(&self) -> Option<usize> {
+        self.cycle_start.map(|start| self.rounds.len() - start)
+    }
+
+    /// The number of times each of the `monkeys` inspects the item during the first
+    /// `rounds` rounds, or `None` if more rounds were asked for than traced without
+    /// finding a cycle
+    pub fn inspections(&self, monkeys: usize, rounds: usize) -> Option<Vec<u64>> {
+        let mut inspections = vec![0; monkeys];
+        let mut count = |traced: &[Vec<usize>], times: u64| {
+            for monkey in traced.iter().flatten() {
+                inspections[*monkey] += times;
+            }
+        };
+
+        match self.cycle_start {
+            _ if rounds <= self.rounds.len() => count(&self.rounds[..rounds], 1),
+            None => return None,
+            Some(start) => {
+                // The rounds before the cycle, every full cycle, then the start of the
+                // cycle for the rounds left
+                let len = self.rounds.len() - start;
+                let cycles = (rounds - start) / len;
+                let rest = (rounds - start) % len;
+                count(&self.rounds[..start], 1);
+                count(&self.rounds[start..], cycles as u64);
+                count(&self.rounds[start..start + rest], 1);
+            }
+        }
+
+        Some(inspections)
+    }
+}
+
+impl std::fmt::Display for Route {
+    /// The route as `0 3 | 1 | (2 | 0 3)`: the monkeys inspecting the item in each
+    /// round, with the repeating rounds in parentheses
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (round, monkeys) in self.rounds.iter().enumerate() {
+            if round > 0 {
+                write!(f, " | ")?;
+            }
+            if Some(round) == self.cycle_start {
+                write!(f, "(")?;
+            }
+
+            let monkeys = monkeys.iter().map(ToString::to_string).collect::<Vec<_>>();
+            write!(f, "{}", monkeys.join(" "))?;
+        }
+
+        if self.cycle_start.is_some() {
+            write!(f, ")")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Trace the route of every item for at most `max_rounds` rounds, or until it repeats,
+/// with the `relief` applied after each inspection. Worry levels are `i128`, except
+/// without relief.
+pub fn trace(
+    monkeys: &[Monkey],
+    max_rounds: usize,
+    relief: Relief,
+) -> Result<Vec<Route>, Day11Error> {
+    match relief {
+        Relief::None => trace_with::<BigInt>(monkeys, max_rounds, relief),
+        _ => trace_with::<i128>(monkeys, max_rounds, relief),
+    }
+}
+
+/// Trace with worry levels of type `W`
+fn trace_with<W: Worry + Hash + Eq>(
+    monkeys: &[Monkey],
+    max_rounds: usize,
+    relief: Relief,
+) -> Result<Vec<Route>, Day11Error> {
+    let inspect = inspect::<W>(monkeys, relief)?;
+    let mut routes = Vec::new();
+
+    for (start, monkey) in monkeys.iter().enumerate() {
+        for item in &monkey.items {
+            let mut route = Route {
+                monkey: start,
+                worry: *item,
+                rounds: Vec::new(),
+                cycle_start: None,
+            };
+
+            // The round where each state (holding monkey and worry level) was seen at
+            // the start of a round
+            let mut seen = HashMap::new();
+            let mut holder = start;
+            let mut worry = W::from_u64(*item);
+
+            while route.rounds.len() < max_rounds {
+                if let Some(round) = seen.insert((holder, worry.clone()), route.rounds.len()) {
+                    route.cycle_start = Some(round);
+                    break;
+                }
+
+                // Follow the item until it is thrown to a monkey that already played
+                let mut inspected_by = Vec::new();
+                loop {
+                    let monkey = &monkeys[holder];
+                    inspected_by.push(holder);
+                    worry = inspect(&monkey.operation, &worry)?;
+
+                    let target = if worry.divisible_by(monkey.test_val) {
+                        monkey.true_monkey
+                    } else {
+                        monkey.false_monkey
+                    };
+
+                    let next_round = target < holder;
+                    holder = target;
+                    if next_round {
+                        break;
+                    }
+                }
+
+                route.rounds.push(inspected_by);
+            }
+
+            routes.push(route);
+        }
+    }
+
+    Ok(routes)
+}
+
+/// The number of items each of the `monkeys` inspects during the first `rounds`
+/// rounds, extrapolated from the cycles of the `routes`
+pub fn extrapolate(
+    routes: &[Route],
+    monkeys: usize,
+    rounds: usize,
+) -> Result<Vec<u64>, Day11Error> {
+    let mut inspections = vec![0; monkeys];
+
+    for route in routes {
+        let counts = route
+            .inspections(monkeys, rounds)
+            .ok_or(Day11Error::RouteTooShort(route.monkey, route.worry))?;
+        for (total, count) in inspections.iter_mut().zip(counts) {
+            *total += count;
+        }
+    }
+
+    Ok(inspections)
+}
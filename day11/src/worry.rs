@@ -0,0 +1,134 @@
+//! The integer types worry levels can be tracked with
+//!
+//! Worry levels are integers that can go below zero, like `old - 3` on a worry level
+//! of 1. Division rounds down, towards negative infinity, and negative worry levels
+//! are tested for divisibility like positive ones, so every type agrees on every test.
+
+use num_bigint::BigInt;
+
+use crate::expr::BinaryOp;
+
+/// A worry level integer
+pub trait Worry: Clone + std::fmt::Display {
+    /// Convert a starting worry level
+    fn from_u64(value: u64) -> Self;
+
+    /// Apply `op` to `self` and `right`, or `None` if the result is out of range
+    fn apply(&self, op: BinaryOp, right: &Self) -> Option<Self>;
+
+    /// Apply `op` to `self` and `right` modulo `modulus`, giving a result between zero
+    /// and `modulus`. Only adding, subtracting and multiplying keep remainders.
+    fn apply_mod(&self, op: BinaryOp, right: &Self, modulus: u64) -> Self;
+
+    /// Divide by `divisor`, rounding down
+    fn div_u64(&self, divisor: u64) -> Self;
+
+    /// The remainder of the division by `divisor`, between zero and `divisor`
+    fn rem_u64(&self, divisor: u64) -> Self;
+
+    /// Returns `true` if `divisor` divides the worry level
+    fn divisible_by(&self, divisor: u64) -> bool;
+}
+
+/// Worry levels of any `u64` starting level, with room for the products of remainders
+/// modulo a `u64`
+impl Worry for i128 {
+    fn from_u64(value: u64) -> Self {
+        i128::from(value)
+    }
+
+    fn apply(&self, op: BinaryOp, right: &Self) -> Option<Self> {
+        match op {
+            BinaryOp::Add => self.checked_add(*right),
+            BinaryOp::Sub => self.checked_sub(*right),
+            BinaryOp::Mul => self.checked_mul(*right),
+            BinaryOp::Div => {
+                // Round towards negative infinity rather than zero
+                let quotient = self.checked_div(*right)?;
+                let inexact = self % right != 0 && (*self < 0) != (*right < 0);
+                Some(quotient - i128::from(inexact))
+            }
+        }
+    }
+
+    fn apply_mod(&self, op: BinaryOp, right: &Self, modulus: u64) -> Self {
+        // Both remainders are below 2⁶⁴, so their product fits a `u128`
+        let modulus = u128::from(modulus);
+        let left = self.rem_euclid(modulus as i128) as u128;
+        let right = right.rem_euclid(modulus as i128) as u128;
+        let result = match op {
+            BinaryOp::Add => left + right,
+            BinaryOp::Sub => left + modulus - right,
+            BinaryOp::Mul => left * right,
+            BinaryOp::Div => unreachable!("division doesn't keep remainders"),
+        };
+
+        (result % modulus) as i128
+    }
+
+    fn div_u64(&self, divisor: u64) -> Self {
+        self.div_euclid(i128::from(divisor))
+    }
+
+    fn rem_u64(&self, divisor: u64) -> Self {
+        self.rem_euclid(i128::from(divisor))
+    }
+
+    fn divisible_by(&self, divisor: u64) -> bool {
+        self.rem_u64(divisor) == 0
+    }
+}
+
+/// Arbitrary precision worry levels, only out of range dividing by zero
+impl Worry for BigInt {
+    fn from_u64(value: u64) -> Self {
+        BigInt::from(value)
+    }
+
+    fn apply(&self, op: BinaryOp, right: &Self) -> Option<Self> {
+        match op {
+            BinaryOp::Add => Some(self + right),
+            BinaryOp::Sub => Some(self - right),
+            BinaryOp::Mul => Some(self * right),
+            BinaryOp::Div if *right == BigInt::ZERO => None,
+            BinaryOp::Div => {
+                // Round towards negative infinity rather than zero
+                let quotient = self / right;
+                let inexact = self % right != BigInt::ZERO
+                    && (*self < BigInt::ZERO) != (*right < BigInt::ZERO);
+                Some(if inexact { quotient - 1 } else { quotient })
+            }
+        }
+    }
+
+    fn apply_mod(&self, op: BinaryOp, right: &Self, modulus: u64) -> Self {
+        let left = self.rem_u64(modulus);
+        let right = right.rem_u64(modulus);
+        let result = match op {
+            BinaryOp::Add => left + right,
+            BinaryOp::Sub => left + modulus - right,
+            BinaryOp::Mul => left * right,
+            BinaryOp::Div => unreachable!("division doesn't keep remainders"),
+        };
+
+        result % modulus
+    }
+
+    fn div_u64(&self, divisor: u64) -> Self {
+        self.apply(BinaryOp::Div, &BigInt::from(divisor))
+            .expect("The relief never divides by zero")
+    }
+
+    fn rem_u64(&self, divisor: u64) -> Self {
+        let remainder = self % divisor;
+        if remainder < BigInt::ZERO {
+            remainder + divisor
+        } else {
+            remainder
+        }
+    }
+
+    fn divisible_by(&self, divisor: u64) -> bool {
+        self % divisor == BigInt::ZERO
+    }
+}
@@ -1,7 +1,10 @@
+use aoc2022::interval::{Interval, IntervalSet};
 use rand::prelude::*;
 
-const INPUT: &str = include_str!("/home/user/workspace/chris-aoc2022/2022/15/input.txt");
-// const INPUT: &str = include_str!("../input");
+const INPUT: &str = include_str!("../input");
+
+/// An (x, y) coordinate on the sensor grid
+type Coord = (isize, isize);
 
 /// Calculate the Manhattan distance
 fn manhattan_distance(first: (isize, isize), second: (isize, isize)) -> isize {
@@ -9,6 +12,31 @@ fn manhattan_distance(first: (isize, isize), second: (isize, isize)) -> isize {
         + (first.1.max(second.1) - first.1.min(second.1)).abs()
 }
 
+/// Calculate the columns of `row` that are within range of at least one sensor
+fn row_coverage(pairs: &[(Coord, Coord)], row: isize) -> IntervalSet {
+    let mut coverage = IntervalSet::new();
+
+    for (sensor, beacon) in pairs {
+        // Calculate the manhattan distance to know the maximum distance from this sensor
+        let distance = manhattan_distance(*sensor, *beacon);
+
+        // Check if this sensor can reach the requested row
+        let remaining = distance - (row - sensor.1).abs();
+        if remaining < 0 {
+            continue;
+        }
+
+        let curr_min_x = sensor.0 - remaining;
+        let curr_max_x = sensor.0 + remaining;
+
+        if let Some(interval) = Interval::new(curr_min_x as i64, curr_max_x as i64) {
+            coverage.insert(interval);
+        }
+    }
+
+    coverage
+}
+
 fn rdtsc() -> u64 {
     unsafe { std::arch::x86_64::_rdtsc() }
 }
@@ -16,9 +44,6 @@ fn rdtsc() -> u64 {
 fn main() {
     let wanted_row = 2000000;
 
-    let mut min_x = 0;
-    let mut max_x = 0;
-
     let mut pairs = Vec::new();
 
     let start = std::time::Instant::now();
@@ -50,6 +75,20 @@ fn main() {
         pairs.push(((sensor_x, sensor_y), (beacon_x, beacon_y)));
     }
 
+    // Solve for part 1: every covered column in the wanted row that isn't already a
+    // known beacon
+    let coverage = row_coverage(&pairs, wanted_row);
+    let mut beacons_in_row = pairs
+        .iter()
+        .map(|(_, beacon)| *beacon)
+        .filter(|(beacon_x, beacon_y)| {
+            *beacon_y == wanted_row && coverage.contains(*beacon_x as i64)
+        })
+        .collect::<Vec<_>>();
+    beacons_in_row.sort_unstable();
+    beacons_in_row.dedup();
+    let part1 = coverage.total_len() - beacons_in_row.len() as u128;
+
     let mut missing_beacon_x = 2000000;
     let mut missing_beacon_y = 2000000;
 
@@ -71,18 +110,6 @@ fn main() {
 
             // println!("{sensor_x},{sensor_y} | {beacon_x},{beacon_y} || {distance}");
 
-            // Check if this sensor can reach the requested row
-            let distance_to_wanted_row = (wanted_row - sensor_y).abs();
-
-            // Solve for part 1
-            if distance_to_wanted_row <= distance {
-                let curr_min_x = sensor_x - (distance - distance_to_wanted_row);
-                let curr_max_x = sensor_x + (distance - distance_to_wanted_row);
-
-                min_x = min_x.min(curr_min_x);
-                max_x = max_x.max(curr_max_x);
-            }
-
             if (*sensor_x, *sensor_y) == (missing_beacon_x, missing_beacon_y) {
                 missing_beacon_x += 1;
                 // missing_beacon_x += 2;
@@ -99,6 +126,9 @@ fn main() {
                 let horiz_dist = (missing_beacon_x - sensor_x).abs();
                 let movement = (distance - vert_dist - horiz_dist).max(1);
 
+                // `is_multiple_of` would read as a divisibility test rather than the 1 in
+                // 4 chance of nudging the test point that this is
+                #[allow(clippy::manual_is_multiple_of)]
                 if (movement == 1 && rdtsc() % 4 == 0) || movement > 1 {
                     if *sensor_x > missing_beacon_x && missing_beacon_x > movement {
                         // Move the test point left
@@ -111,6 +141,9 @@ fn main() {
                     }
                 }
 
+                // `is_multiple_of` would read as a divisibility test rather than the 1 in
+                // 4 chance of nudging the test point that this is
+                #[allow(clippy::manual_is_multiple_of)]
                 if (movement == 1 && rdtsc() % 4 == 0) || movement > 1 {
                     if *sensor_y > missing_beacon_y && missing_beacon_y > movement {
                         // Move the test point up
@@ -136,7 +169,7 @@ fn main() {
         }
     }
 
-    println!("Part 1 Row: {}", max_x - min_x);
+    println!("Part 1 Row: {part1}");
 
     println!(
         "Part 2 Missing beacon: {}",
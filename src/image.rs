@@ -0,0 +1,81 @@
+//! Write grids of colored cells as scaled up RGB PNGs
+//!
+//! Used for the forest heatmaps (day08), rope traces (day09) and CRT frames (day10)
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+/// An RGB color
+pub type Rgb = [u8; 3];
+
+/// Errors writing an image
+#[derive(Debug)]
+pub enum ImageError {
+    /// The scaled up image doesn't fit the dimensions of a PNG
+    TooLarge,
+
+    /// Creating or writing the file failed
+    Io(std::io::Error),
+
+    /// Encoding the PNG failed
+    Png(png::EncodingError),
+}
+
+impl std::fmt::Display for ImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageError::TooLarge => write!(f, "the image is too large for a PNG"),
+            ImageError::Io(err) => write!(f, "{err}"),
+            ImageError::Png(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+/// Write a `width` by `height` grid of cells as an RGB PNG to `path`, where each cell
+/// is drawn as a `scale` by `scale` square of the color given by `color(x, y)`
+///
+/// # Errors
+///
+/// Returns [`ImageError::TooLarge`] if the scaled up image is wider or taller than a
+/// PNG allows, or the error creating the file or encoding the image
+pub fn write_rgb_png(
+    path: &Path,
+    width: usize,
+    height: usize,
+    scale: usize,
+    color: impl Fn(usize, usize) -> Rgb,
+) -> Result<(), ImageError> {
+    let size = |cells: usize| {
+        cells
+            .checked_mul(scale)
+            .and_then(|pixels| u32::try_from(pixels).ok())
+            .ok_or(ImageError::TooLarge)
+    };
+    let (image_width, image_height) = (size(width)?, size(height)?);
+
+    let mut data = Vec::new();
+    for y in 0..height {
+        let mut line = Vec::with_capacity(width * scale * 3);
+        for x in 0..width {
+            let pixel = color(x, y);
+            for _ in 0..scale {
+                line.extend(pixel);
+            }
+        }
+
+        for _ in 0..scale {
+            data.extend(&line);
+        }
+    }
+
+    let file = File::create(path).map_err(ImageError::Io)?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), image_width, image_height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header().map_err(ImageError::Png)?;
+    writer.write_image_data(&data).map_err(ImageError::Png)
+}
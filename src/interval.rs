@@ -0,0 +1,318 @@
+//! Inclusive integer intervals and sets of disjoint intervals
+//!
+//! Used for section assignments (day04) and sensor coverage on a row (day15)
+
+/// An inclusive range of integers `start..=end`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval {
+    /// The first value in the interval
+    start: i64,
+
+    /// The last value in the interval
+    end: i64,
+}
+
+impl Interval {
+    /// Create the interval `start..=end`, or `None` if `start > end`
+    #[must_use]
+    pub const fn new(start: i64, end: i64) -> Option<Self> {
+        if start > end {
+            return None;
+        }
+
+        Some(Self { start, end })
+    }
+
+    /// The first value in the interval
+    #[must_use]
+    pub const fn start(&self) -> i64 {
+        self.start
+    }
+
+    /// The last value in the interval
+    #[must_use]
+    pub const fn end(&self) -> i64 {
+        self.end
+    }
+
+    /// The number of values in the interval, as a `u128` since `i64::MIN..=i64::MAX`
+    /// holds one more value than a `u64` can count
+    #[must_use]
+    pub const fn len(&self) -> u128 {
+        self.end.abs_diff(self.start) as u128 + 1
+    }
+
+    /// An interval always contains at least one value
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Returns `true` if `value` is in the interval
+    #[must_use]
+    pub const fn contains(&self, value: i64) -> bool {
+        self.start <= value && value <= self.end
+    }
+
+    /// Returns `true` if every value of `other` is also in this interval
+    #[must_use]
+    pub const fn contains_interval(&self, other: &Interval) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    /// Returns `true` if this interval and `other` share at least one value
+    #[must_use]
+    pub const fn overlaps(&self, other: &Interval) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    /// Returns `true` if this interval and `other` overlap or sit next to each other,
+    /// meaning their union is a single interval
+    #[must_use]
+    pub const fn touches(&self, other: &Interval) -> bool {
+        self.start <= other.end.saturating_add(1) && other.start <= self.end.saturating_add(1)
+    }
+
+    /// The values in both this interval and `other`
+    #[must_use]
+    pub fn intersection(&self, other: &Interval) -> Option<Interval> {
+        Interval::new(self.start.max(other.start), self.end.min(other.end))
+    }
+
+    /// The values in either this interval or `other`
+    #[must_use]
+    pub fn union(&self, other: &Interval) -> IntervalSet {
+        [*self, *other].into_iter().collect()
+    }
+
+    /// The values in this interval but not in `other`
+    #[must_use]
+    pub fn difference(&self, other: &Interval) -> IntervalSet {
+        let mut result = IntervalSet::new();
+
+        if !self.overlaps(other) {
+            result.insert(*self);
+            return result;
+        }
+
+        // The piece to the left of `other`
+        if other.start > self.start {
+            result.intervals.push(Interval {
+                start: self.start,
+                end: other.start - 1,
+            });
+        }
+
+        // The piece to the right of `other`
+        if other.end < self.end {
+            result.intervals.push(Interval {
+                start: other.end + 1,
+                end: self.end,
+            });
+        }
+
+        result
+    }
+}
+
+impl std::fmt::Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+/// A set of integers stored as sorted, disjoint, non-adjacent intervals
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet {
+    /// The intervals in the set, sorted by `start`. Overlapping or adjacent intervals
+    /// are always merged on insert.
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    /// Create an empty set
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            intervals: Vec::new(),
+        }
+    }
+
+    /// The merged intervals of this set, sorted by `start`
+    #[must_use]
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    /// Iterate over the merged intervals of this set
+    pub fn iter(&self) -> std::slice::Iter<'_, Interval> {
+        self.intervals.iter()
+    }
+
+    /// Returns `true` if the set contains no values
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The total number of values in the set. The intervals are disjoint, so the sum
+    /// is at most the 2⁶⁴ values of an `i64` and can't overflow.
+    #[must_use]
+    pub fn total_len(&self) -> u128 {
+        self.intervals.iter().map(Interval::len).sum()
+    }
+
+    /// Add every value of `interval` to the set, merging it with any overlapping or
+    /// adjacent intervals
+    pub fn insert(&mut self, interval: Interval) {
+        // Find the range of existing intervals that merge with the new one
+        let first = self
+            .intervals
+            .partition_point(|curr| curr.end.saturating_add(1) < interval.start);
+        let last = first
+            + self.intervals[first..]
+                .iter()
+                .take_while(|curr| curr.touches(&interval))
+                .count();
+
+        let mut merged = interval;
+        if let (Some(left), Some(right)) = (
+            self.intervals[first..last].first(),
+            self.intervals[first..last].last(),
+        ) {
+            merged.start = merged.start.min(left.start);
+            merged.end = merged.end.max(right.end);
+        }
+
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    /// Returns `true` if `value` is in the set
+    #[must_use]
+    pub fn contains(&self, value: i64) -> bool {
+        let index = self.intervals.partition_point(|curr| curr.end < value);
+        self.intervals
+            .get(index)
+            .is_some_and(|curr| curr.contains(value))
+    }
+
+    /// Returns `true` if every value of `interval` is in the set
+    #[must_use]
+    pub fn contains_interval(&self, interval: &Interval) -> bool {
+        let index = self
+            .intervals
+            .partition_point(|curr| curr.end < interval.start);
+        self.intervals
+            .get(index)
+            .is_some_and(|curr| curr.contains_interval(interval))
+    }
+
+    /// Returns `true` if any value of `interval` is in the set
+    #[must_use]
+    pub fn overlaps(&self, interval: &Interval) -> bool {
+        let index = self
+            .intervals
+            .partition_point(|curr| curr.end < interval.start);
+        self.intervals
+            .get(index)
+            .is_some_and(|curr| curr.overlaps(interval))
+    }
+
+    /// The values in either this set or `other`
+    #[must_use]
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        let mut result = self.clone();
+        for interval in other {
+            result.insert(*interval);
+        }
+
+        result
+    }
+
+    /// The values in both this set and `other`
+    #[must_use]
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut result = IntervalSet::new();
+
+        // Walk both sorted lists, advancing whichever interval ends first
+        let (mut left, mut right) = (0, 0);
+        while let (Some(curr_left), Some(curr_right)) =
+            (self.intervals.get(left), other.intervals.get(right))
+        {
+            if let Some(both) = curr_left.intersection(curr_right) {
+                result.intervals.push(both);
+            }
+
+            if curr_left.end < curr_right.end {
+                left += 1;
+            } else {
+                right += 1;
+            }
+        }
+
+        result
+    }
+
+    /// The values in this set but not in `other`
+    #[must_use]
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut result = IntervalSet::new();
+
+        for interval in &self.intervals {
+            let mut remaining = IntervalSet::new();
+            remaining.insert(*interval);
+
+            // Only the intervals of `other` overlapping this one can remove values
+            let first = other
+                .intervals
+                .partition_point(|curr| curr.end < interval.start);
+            for removed in other.intervals[first..]
+                .iter()
+                .take_while(|curr| curr.start <= interval.end)
+            {
+                remaining = remaining
+                    .iter()
+                    .flat_map(|curr| curr.difference(removed).intervals)
+                    .collect();
+            }
+
+            result.intervals.extend(remaining.intervals);
+        }
+
+        result
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Interval>>(iter: I) -> Self {
+        let mut result = IntervalSet::new();
+        for interval in iter {
+            result.insert(interval);
+        }
+
+        result
+    }
+}
+
+impl<'a> IntoIterator for &'a IntervalSet {
+    type Item = &'a Interval;
+    type IntoIter = std::slice::Iter<'a, Interval>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.intervals.iter()
+    }
+}
+
+impl std::fmt::Display for IntervalSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (index, interval) in self.intervals.iter().enumerate() {
+            if index > 0 {
+                write!(f, ",")?;
+            }
+
+            write!(f, "{interval}")?;
+        }
+
+        Ok(())
+    }
+}